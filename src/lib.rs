@@ -1,112 +1,709 @@
 //! This is a fork of the datepicker from `egui_extras` (https://github.com/emilk/egui/tree/master/crates/egui_extras)
 
-use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::{cell::RefCell, fmt::Write as _, ops::RangeInclusive, sync::Arc};
+
+use chrono::{
+    format::{Parsed, StrftimeItems},
+    Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday,
+};
 use egui::{
-    Align, Area, Button, Color32, ComboBox, Direction, Frame, Id, InnerResponse, Key, Layout,
-    Order, RichText, Ui, Vec2, Widget,
+    cache::{ComputerMut, FrameCache},
+    style::ScrollAnimation,
+    util::IdTypeMap,
+    Align, Align2, Area, Button, Color32, ComboBox, Direction, DragValue, Frame, Id, Image,
+    InnerResponse, Key, Layout, Modifiers, Order, Rect, Response, Rgba, RichText, ScrollArea,
+    Stroke, StrokeKind, Ui, Vec2, Widget, WidgetInfo, WidgetType,
 };
-use egui_extras::{Column, Size, StripBuilder, TableBuilder};
+use egui_extras::{Column, Size, StripBuilder, TableBody, TableBuilder};
+
+/// A row of the calendar, see [`calendar_weeks`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Week {
+    /// The week number, according to the [`WeekNumbering`].
+    pub number: u8,
+
+    /// The year the week number counts in. Around New Year this is not the year of all days,
+    /// e.g. ISO week 1 of 2025 starts on December 30, 2024.
+    pub year: i32,
 
-#[derive(Debug)]
-struct Week {
-    number: u8,
-    days: Vec<NaiveDate>,
+    /// The seven days from Monday to Sunday.
+    pub days: Vec<NaiveDate>,
 }
 
-fn month_data(year: i32, month: u32) -> Vec<Week> {
-    let first = NaiveDate::from_ymd_opt(year, month, 1).expect("Could not create NaiveDate");
-    let mut start = first;
-    while start.weekday() != Weekday::Mon {
-        start = start.checked_sub_signed(Duration::days(1)).unwrap();
+/// How the weeks of the calendar are numbered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum WeekNumbering {
+    /// ISO 8601: weeks start on Monday and week 1 is the week with the first Thursday of the
+    /// year, so the first days of January can belong to week 52 or 53.
+    #[default]
+    Iso,
+
+    /// Common in the US: weeks start on Sunday and week 1 is the week containing January 1st.
+    /// The calendar rows start on Monday, so a row gets the number of its Monday.
+    UsCommon,
+}
+
+impl WeekNumbering {
+    /// The year the week of `date` counts in, see [`Week::year`].
+    fn week_year(self, date: NaiveDate) -> i32 {
+        match self {
+            Self::Iso => date.iso_week().year(),
+            Self::UsCommon => {
+                let saturday =
+                    date + Duration::days(6 - date.weekday().num_days_from_sunday() as i64);
+                saturday.year()
+            }
+        }
+    }
+
+    fn week_number(self, date: NaiveDate) -> u8 {
+        match self {
+            Self::Iso => date.iso_week().week() as u8,
+            Self::UsCommon => {
+                let saturday =
+                    date + Duration::days(6 - date.weekday().num_days_from_sunday() as i64);
+                if saturday.year() > date.year() {
+                    // This week contains January 1st of the next year
+                    return 1;
+                }
+                let first_sunday_offset = date
+                    .with_ordinal(1)
+                    .expect("Could not create NaiveDate")
+                    .weekday()
+                    .num_days_from_sunday();
+                ((date.ordinal0() + first_sunday_offset) / 7 + 1) as u8
+            }
+        }
     }
-    let mut weeks = vec![];
-    let mut week = vec![];
-    while start < first || start.month() == first.month() || start.weekday() != Weekday::Mon {
-        week.push(start);
+}
 
-        if start.weekday() == Weekday::Sun {
-            weeks.push(Week {
-                number: start.iso_week().week() as u8,
-                days: std::mem::take(&mut week),
-            });
+/// Returns the weeks the calendar shows for a month, for drawing a custom calendar.
+///
+/// These are always full weeks from the Monday on or before the first day of the month to
+/// the Sunday on or after its last day, so they include days of the adjacent months.
+/// Depending on the month that makes 4 to 6 weeks.
+///
+/// Returns `None` if `month` is not within `1..=12` or its weeks reach out of the range of
+/// [`NaiveDate`], which can happen in the first and last year of the range.
+pub fn calendar_weeks(year: i32, month: u32, week_numbering: WeekNumbering) -> Option<Vec<Week>> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+
+    let start =
+        first.checked_sub_signed(Duration::days(first.weekday().num_days_from_monday() as i64))?;
+    let end = last.checked_add_signed(Duration::days(
+        6 - last.weekday().num_days_from_monday() as i64,
+    ))?;
+    // 4 weeks for a February of 28 days starting on Monday, 6 for e.g. a month of 31 days
+    // starting on Saturday. The popup sizes the calendar from this count.
+    let week_count = ((end - start).num_days() + 1) / 7;
+
+    let weeks = (0..week_count)
+        .map(|week| {
+            let monday = start + Duration::weeks(week);
+            Week {
+                number: week_numbering.week_number(monday),
+                year: week_numbering.week_year(monday),
+                days: (0..7).map(|day| monday + Duration::days(day)).collect(),
+            }
+        })
+        .collect();
+    Some(weeks)
+}
+
+/// Moves `date` into the range from `min` to `max`, the way the popup does with
+/// [`DatePickerButton::min_date`] and [`DatePickerButton::max_date`]. Either bound may be
+/// missing. If `min` is after `max`, `min` wins.
+pub fn clamp_date(date: NaiveDate, min: Option<NaiveDate>, max: Option<NaiveDate>) -> NaiveDate {
+    let date = max.map_or(date, |max| date.min(max));
+    min.map_or(date, |min| date.max(min))
+}
+
+/// Parses a date in `format`, e.g. the text of a field next to a [`DatePickerButton`]. Time
+/// fields of the format are checked but left out of the result. If the format has no day, like
+/// the default ones of a month or a year [`Precision`], the day and a missing month are taken to
+/// be the first.
+pub fn parse(format: &str, text: &str) -> Option<NaiveDate> {
+    let mut parsed = Parsed::new();
+    chrono::format::parse(&mut parsed, text, StrftimeItems::new(format)).ok()?;
+    if parsed.day().is_none() && parsed.to_naive_date().is_err() {
+        if parsed.month().is_none() {
+            parsed.set_month(1).ok()?;
+        }
+        parsed.set_day(1).ok()?;
+    }
+    parsed.to_naive_date().ok()
+}
+
+#[derive(Default)]
+struct MonthDataComputer;
+
+impl ComputerMut<(i32, u32, WeekNumbering), Arc<Vec<Week>>> for MonthDataComputer {
+    fn compute(
+        &mut self,
+        (year, month, week_numbering): (i32, u32, WeekNumbering),
+    ) -> Arc<Vec<Week>> {
+        // The popup only shows months of the `supported_years`, which always have weeks
+        Arc::new(calendar_weeks(year, month, week_numbering).unwrap_or_default())
+    }
+}
+
+/// Keeps the weeks of the shown month around as long as the popup keeps showing it.
+///
+/// Computing a month allocates the outer `Vec` plus one `Vec` per week (7 allocations for
+/// a month spanning 6 weeks). Without the cache that happened every frame the popup was
+/// open, with it only when the shown month changes; a cache hit just bumps the `Arc`.
+type MonthDataCache = FrameCache<Arc<Vec<Week>>, MonthDataComputer>;
+
+/// A labeled shortcut that computes a date relative to today.
+pub type Preset = (&'static str, fn(NaiveDate) -> NaiveDate);
+
+/// A basic set of [`Preset`]s for [`DatePickerButton::presets`].
+pub const DEFAULT_PRESETS: &[Preset] = &[
+    ("Today", |today| today),
+    ("Yesterday", |today| today.pred_opt().unwrap_or(today)),
+    ("Start of week", |today| {
+        today - Duration::days(today.weekday().num_days_from_monday() as i64)
+    }),
+    ("First of month", |today| today.with_day(1).unwrap_or(today)),
+    ("End of month", |today| {
+        today
+            .with_day(1)
+            .and_then(|first| first.checked_add_months(Months::new(1)))
+            .and_then(|next| next.pred_opt())
+            .unwrap_or(today)
+    }),
+];
+
+const PRESETS_WIDTH: f32 = 100.0;
+
+/// Below this the combo boxes and the calendar no longer fit.
+const MIN_POPUP_WIDTH: f32 = 150.0;
+
+/// Below this the text of the rows gets cut off.
+const MIN_ROW_HEIGHT: f32 = 10.0;
+
+/// Where the popup opens relative to the date picker button, or its
+/// [`DatePickerButton::anchor`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Placement {
+    /// Below the button.
+    Below,
+
+    /// Above the button.
+    Above,
+
+    /// Below the button, or above it if there is not enough room below.
+    /// If it fits on neither side, the side with more room is used.
+    #[default]
+    Auto,
+}
+
+/// How today's date is marked in the calendar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TodayMarker {
+    /// A circle around the day.
+    Circle {
+        /// Radius of the circle; by default it scales with the row height.
+        radius: Option<f32>,
+
+        /// Color of the circle; by default the text color of inactive widgets.
+        color: Option<Color32>,
+    },
+
+    /// The day filled with a color. The selection fill takes precedence if today is also the
+    /// selected day.
+    Fill(Color32),
+
+    /// A line below the day number.
+    Underline,
+
+    /// The day number in the strong text color.
+    Bold,
+
+    /// Today is not marked.
+    None,
+}
+
+/// What a [`DatePickerButton`] picks: a day, or just a month or a year.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    /// A day, picked in the calendar.
+    #[default]
+    Day,
+
+    /// A month, e.g. for the expiry date of a credit card. There is no calendar and the saved
+    /// date is the first day of the month that can be picked.
+    Month,
+
+    /// A year. There is no calendar and the saved date is the first day of the year that can
+    /// be picked.
+    Year,
+}
+
+impl Precision {
+    /// Whether the popup offers `step`, i.e. it does not move by less than the precision.
+    fn allows(self, step: NavigationStep) -> bool {
+        match self {
+            Self::Day => true,
+            Self::Month => !matches!(step, NavigationStep::PreviousDay | NavigationStep::NextDay),
+            Self::Year => matches!(
+                step,
+                NavigationStep::PreviousYear | NavigationStep::NextYear
+            ),
+        }
+    }
+}
+
+/// How the selected day is marked in the calendar.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionStyle {
+    /// The day is filled with the selection color, covering weekend and highlight colors.
+    #[default]
+    Fill,
+
+    /// A line in the selection color around the day, keeping its weekend or highlight color.
+    Outline,
+}
+
+/// How the weekdays are labeled above the calendar.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeekdayLabelStyle {
+    /// The names set with [`DatePickerButton::day_names`], e.g. "Mo".
+    #[default]
+    Short,
+
+    /// The first letter of the names set with [`DatePickerButton::day_names`], e.g. "M".
+    Narrow,
+
+    /// The names set with [`DatePickerButton::full_day_names`], e.g. "Monday".
+    /// These usually need a wider popup, see [`DatePickerButton::popup_width`].
+    Full,
+}
+
+impl Default for TodayMarker {
+    fn default() -> Self {
+        Self::Circle {
+            radius: None,
+            color: None,
         }
-        start = start.checked_add_signed(Duration::days(1)).unwrap();
     }
+}
 
-    weeks
+/// What is special about a day in the calendar, passed to [`DatePickerButton::day_ui`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DayState {
+    /// The day is the one currently picked in the popup.
+    pub selected: bool,
+
+    /// The day is today.
+    pub today: bool,
+
+    /// The day is a Saturday or Sunday.
+    pub weekend: bool,
+
+    /// The day belongs to the previous or next month.
+    pub other_month: bool,
+
+    /// The day can be picked.
+    pub enabled: bool,
+
+    /// The day is the [`DatePickerButton::secondary_date`].
+    pub secondary: bool,
 }
 
-#[derive(Default, Clone)]
-pub(crate) struct DatePickerButtonState {
+type DayUi<'a> = dyn Fn(&mut Ui, NaiveDate, DayState) -> Response + 'a;
+
+type DayHighlight<'a> = dyn Fn(NaiveDate) -> Option<Color32> + 'a;
+
+type DayDecorator<'a> = dyn Fn(NaiveDate, &mut Ui, Rect) + 'a;
+
+type DayNumberFormat<'a> = dyn Fn(u32) -> String + 'a;
+
+type ButtonLabel<'a> = dyn Fn(NaiveDate) -> String + 'a;
+
+type OnMonthChanged<'a> = dyn FnMut(i32, u32) + 'a;
+
+/// State of a [`DatePickerButton`], stored persisted in `ui.data` under the button id.
+///
+/// See [`set_open`] for how to get the id.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct DatePickerButtonState {
+    /// Whether the popup is open.
     pub picker_visible: bool,
 }
 
+/// Opens or closes the popup of the date picker button with the given `id`.
+///
+/// The id is the one set with [`DatePickerButton::id`]. Otherwise the button derives it
+/// from its [`DatePickerButton::id_salt`], i.e. `ui.make_persistent_id(Some(id_salt))` in
+/// the `Ui` the button is added to, or `ui.make_persistent_id(None::<&str>)` if no salt
+/// was set.
+pub fn set_open(ui: &Ui, id: Id, open: bool) {
+    ui.data_mut(|data| {
+        data.get_persisted_mut_or_default::<DatePickerButtonState>(id)
+            .picker_visible = open;
+        if !open {
+            reset_popup(data, id);
+        }
+    });
+}
+
+/// Returns `true` if the popup of the date picker button with the given `id` is open.
+///
+/// See [`set_open`] for how to get the `id`.
+pub fn is_open(ui: &Ui, id: Id) -> bool {
+    ui.data_mut(|data| data.get_persisted::<DatePickerButtonState>(id))
+        .is_some_and(|state| state.picker_visible)
+}
+
+enum Selection<'a> {
+    Date(&'a mut NaiveDate),
+    DateTime(&'a mut NaiveDateTime),
+    Accessors {
+        get: Box<dyn Fn() -> NaiveDate + 'a>,
+        set: Box<dyn FnMut(NaiveDate) + 'a>,
+    },
+}
+
 /// Shows a date, and will open a date picker popup when clicked.
-pub struct DatePickerButton<'a> {
-    selection: &'a mut NaiveDate,
+///
+/// While the popup is open, these keys can be used:
+/// - `T`: go to today
+/// - `Home` and `End`: go to the first and the last day of the month
+/// - `Escape`: close the popup without saving, see [`Self::close_on_escape`]
+///
+/// Opening the popup from the keyboard focuses the selected day. While a widget of the popup
+/// has keyboard focus, `Tab` and `Shift+Tab` cycle within the popup and the widgets behind it
+/// cannot be used. Closing the popup then returns the focus to the button.
+pub struct DatePickerButton<'a, S = WithSelection<'a>> {
+    selection: S,
+    config: ButtonConfig<'a>,
+}
+
+/// The selection of a [`DatePickerButton`], see [`DatePickerButton::new`].
+pub struct WithSelection<'a>(Selection<'a>);
+
+/// The lack of a selection of a [`DatePickerConfig`].
+pub struct NoSelection;
+
+/// A [`DatePickerButton`] without a selection, to configure once, keep around and show every
+/// frame for a selection with [`DatePickerButton::show_for`]. See [`DatePickerButton::unbound`].
+///
+/// Without a selection it cannot be shown any other way:
+///
+/// ```compile_fail
+/// # fn show(ui: &mut egui::Ui) {
+/// ui.add(egui_extras_datepicker_fork::DatePickerButton::unbound());
+/// # }
+/// ```
+pub type DatePickerConfig<'a> = DatePickerButton<'a, NoSelection>;
+
+/// Everything a [`DatePickerButton`] is configured with, apart from its selection.
+struct ButtonConfig<'a> {
+    /// The selection has a time of day, see [`DatePickerButton::new_with_time`].
+    with_time: bool,
     id_salt: Option<&'a str>,
+    id: Option<Id>,
     combo_boxes: bool,
+    quick_nav: bool,
     arrows: bool,
     calendar: bool,
+    precision: Precision,
     calendar_week: bool,
+    week_column_width: Option<f32>,
+    week_numbering: WeekNumbering,
+    week_show_year: bool,
+    right_to_left: bool,
+    stepper: bool,
     show_icon: bool,
-    format: String,
+    icon: RichText,
+    icon_image: Option<Image<'a>>,
+    format: Option<String>,
+    label: Option<Box<ButtonLabel<'a>>>,
     highlight_weekends: bool,
     day_names: &'static [&'static str],
+    full_day_names: &'static [&'static str],
+    weekday_label_style: WeekdayLabelStyle,
     month_names: &'static [&'static str],
-    save_button_text: &'static str,
-    cancel_button_text: &'static str,
+    save_button_text: String,
+    cancel_button_text: String,
+    show_confirm_buttons: bool,
+    close_on_save: bool,
+    close_on_escape: bool,
+    close_on_click_outside: bool,
+    live_commit: bool,
+    day_decorator: Option<Box<DayDecorator<'a>>>,
+    day_ui: Option<Box<DayUi<'a>>>,
+    day_number_format: Option<Box<DayNumberFormat<'a>>>,
+    day_align: Align,
+    adjacent_day_opacity: f32,
+    disabled_day_opacity: f32,
+    today_marker: TodayMarker,
+    selection_style: SelectionStyle,
+    secondary_date: Option<NaiveDate>,
+    highlighted_dates: Option<Box<DayHighlight<'a>>>,
+    /// In a `RefCell`, as [`DatePickerButton::show_for`] only borrows the button.
+    on_month_changed: Option<RefCell<Box<OnMonthChanged<'a>>>>,
+    presets: &'static [Preset],
+    popup_width: f32,
+    popup_placement: Placement,
+    anchor: Option<Rect>,
+    popup_order: Order,
+    screen_margin: Option<f32>,
+    show_seconds: bool,
+    default_open: bool,
+    enabled: bool,
+    initial_view: Option<(i32, u32)>,
+    remember_view: bool,
+    min_date: Option<NaiveDate>,
+    max_date: Option<NaiveDate>,
+    disable_past: bool,
+    disable_future: bool,
+    selectable_weekdays: &'a [Weekday],
+    week_selectable: bool,
+    animate: bool,
+    show_weekday_header: bool,
+    row_height: f32,
+    cell_spacing: Vec2,
+    popup_spacing: f32,
+    day_hover_text: bool,
+    hover_format: String,
+    week_number_on_hover: bool,
+    show_day_of_year: bool,
+    highlight_hovered_week: bool,
+    frame: Option<Frame>,
 }
 
 impl<'a> DatePickerButton<'a> {
     pub fn new(selection: &'a mut NaiveDate) -> Self {
+        Self::with_selection(WithSelection(Selection::Date(selection)))
+    }
+
+    /// Picks a date and a time of day.
+    /// Hours and minutes, and with [`Self::show_seconds`] seconds, are picked with combo boxes
+    /// below the calendar, and saved together with the date. (Default format: %Y-%m-%d %H:%M)
+    pub fn new_with_time(selection: &'a mut NaiveDateTime) -> Self {
+        let mut button = Self::with_selection(WithSelection(Selection::DateTime(selection)));
+        button.config.with_time = true;
+        button
+    }
+
+    /// Picks a date that is not behind a `&mut`, e.g. one shared in an `Arc<Mutex<_>>`.
+    /// `get` is called once per frame to read the date, `set` when a date is saved.
+    pub fn from_accessors(
+        get: impl Fn() -> NaiveDate + 'a,
+        set: impl FnMut(NaiveDate) + 'a,
+    ) -> Self {
+        Self::with_selection(WithSelection(Selection::Accessors {
+            get: Box::new(get),
+            set: Box::new(set),
+        }))
+    }
+}
+
+impl DatePickerConfig<'_> {
+    /// Creates a [`DatePickerConfig`], a button without a selection, e.g. to configure it once,
+    /// keep it around and show it every frame with [`Self::show_for`].
+    pub fn unbound() -> Self {
+        Self::with_selection(NoSelection)
+    }
+
+    /// Shows the button for `selection`, leaving the configuration as is, so it can be shown
+    /// again the next frame.
+    pub fn show_for(&self, ui: &mut Ui, selection: &mut NaiveDate) -> DatePickerResponse {
+        self.config.show_selection(ui, Selection::Date(selection))
+    }
+}
+
+impl<'a, S> DatePickerButton<'a, S> {
+    fn with_selection(selection: S) -> Self {
         Self {
             selection,
-            id_salt: None,
-            combo_boxes: true,
-            arrows: true,
-            calendar: true,
-            calendar_week: true,
-            show_icon: true,
-            format: "%Y-%m-%d".to_owned(),
-            highlight_weekends: true,
-            day_names: &["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"],
-            month_names: &[
-                "January",
-                "February",
-                "March",
-                "April",
-                "May",
-                "June",
-                "July",
-                "August",
-                "September",
-                "October",
-                "November",
-                "December",
-            ],
-            save_button_text: "Save",
-            cancel_button_text: "Cancel",
+            config: ButtonConfig {
+                with_time: false,
+                id_salt: None,
+                id: None,
+                combo_boxes: true,
+                quick_nav: false,
+                arrows: true,
+                calendar: true,
+                precision: Precision::Day,
+                calendar_week: true,
+                week_column_width: None,
+                week_numbering: WeekNumbering::Iso,
+                week_show_year: false,
+                right_to_left: false,
+                stepper: false,
+                show_icon: true,
+                icon: RichText::new("📆"),
+                icon_image: None,
+                format: None,
+                label: None,
+                highlight_weekends: true,
+                day_names: &["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"],
+                full_day_names: &[
+                    "Monday",
+                    "Tuesday",
+                    "Wednesday",
+                    "Thursday",
+                    "Friday",
+                    "Saturday",
+                    "Sunday",
+                ],
+                weekday_label_style: WeekdayLabelStyle::Short,
+                month_names: &[
+                    "January",
+                    "February",
+                    "March",
+                    "April",
+                    "May",
+                    "June",
+                    "July",
+                    "August",
+                    "September",
+                    "October",
+                    "November",
+                    "December",
+                ],
+                save_button_text: "Save".to_owned(),
+                cancel_button_text: "Cancel".to_owned(),
+                show_confirm_buttons: true,
+                close_on_save: true,
+                close_on_escape: true,
+                close_on_click_outside: true,
+                live_commit: false,
+                day_decorator: None,
+                day_ui: None,
+                day_number_format: None,
+                day_align: Align::Center,
+                adjacent_day_opacity: 0.5,
+                disabled_day_opacity: 1.0,
+                today_marker: TodayMarker::default(),
+                selection_style: SelectionStyle::default(),
+                secondary_date: None,
+                highlighted_dates: None,
+                on_month_changed: None,
+                presets: &[],
+                popup_width: 333.0,
+                popup_placement: Placement::Auto,
+                anchor: None,
+                popup_order: Order::Foreground,
+                screen_margin: None,
+                show_seconds: false,
+                default_open: false,
+                enabled: true,
+                initial_view: None,
+                remember_view: false,
+                min_date: None,
+                max_date: None,
+                disable_past: false,
+                disable_future: false,
+                selectable_weekdays: &[
+                    Weekday::Mon,
+                    Weekday::Tue,
+                    Weekday::Wed,
+                    Weekday::Thu,
+                    Weekday::Fri,
+                    Weekday::Sat,
+                    Weekday::Sun,
+                ],
+                week_selectable: false,
+                animate: false,
+                show_weekday_header: true,
+                row_height: 20.0,
+                cell_spacing: Vec2::new(1.0, 2.0),
+                popup_spacing: 2.0,
+                day_hover_text: false,
+                hover_format: "%A, %B %-d, %Y".to_owned(),
+                week_number_on_hover: false,
+                show_day_of_year: false,
+                highlight_hovered_week: false,
+                frame: None,
+            },
         }
     }
 
     pub fn day_names(mut self, day_names: &'static [&'static str]) -> Self {
-        self.day_names = day_names;
+        self.config.day_names = day_names;
+        self
+    }
+
+    /// Change the full weekday names, starting with Monday, used with
+    /// [`WeekdayLabelStyle::Full`]. (Default: English names)
+    #[inline]
+    pub fn full_day_names(mut self, full_day_names: &'static [&'static str]) -> Self {
+        self.config.full_day_names = full_day_names;
+        self
+    }
+
+    /// Change how the weekdays are labeled above the calendar.
+    /// (Default: [`WeekdayLabelStyle::Short`])
+    #[inline]
+    pub fn weekday_label_style(mut self, weekday_label_style: WeekdayLabelStyle) -> Self {
+        self.config.weekday_label_style = weekday_label_style;
         self
     }
 
     pub fn month_names(mut self, month_names: &'static [&'static str]) -> Self {
-        self.month_names = month_names;
+        self.config.month_names = month_names;
+        self
+    }
+
+    pub fn save_button_text(mut self, text: impl Into<String>) -> Self {
+        self.config.save_button_text = text.into();
         self
     }
 
-    pub fn save_button_text(mut self, text: &'static str) -> Self {
-        self.save_button_text = text;
+    pub fn cancel_button_text(mut self, text: impl Into<String>) -> Self {
+        self.config.cancel_button_text = text.into();
+        self
+    }
+
+    /// Show the Save and Cancel buttons in the popup. (Default: true)
+    /// Without them, clicking a day commits it right away and closes the popup, and any other
+    /// change in the popup commits right away like with [`Self::live_commit`].
+    #[inline]
+    pub fn show_confirm_buttons(mut self, show_confirm_buttons: bool) -> Self {
+        self.config.show_confirm_buttons = show_confirm_buttons;
+        self
+    }
+
+    /// Close the popup when a date is saved, with the Save button or, without confirm buttons,
+    /// by clicking a day. (Default: true)
+    /// Cancel always closes it.
+    #[inline]
+    pub fn close_on_save(mut self, close_on_save: bool) -> Self {
+        self.config.close_on_save = close_on_save;
+        self
+    }
+
+    /// Close the popup without saving when Escape is pressed. (Default: true)
+    /// Turn this off inside a modal that handles Escape itself.
+    #[inline]
+    pub fn close_on_escape(mut self, close_on_escape: bool) -> Self {
+        self.config.close_on_escape = close_on_escape;
+        self
+    }
+
+    /// Close the popup without saving when clicking outside of it. (Default: true)
+    /// With this and [`Self::close_on_escape`] off, the popup only closes by saving or with the
+    /// Cancel button, e.g. for a picker inside a modal with its own closing logic.
+    #[inline]
+    pub fn close_on_click_outside(mut self, close_on_click_outside: bool) -> Self {
+        self.config.close_on_click_outside = close_on_click_outside;
         self
     }
 
-    pub fn cancel_button_text(mut self, text: &'static str) -> Self {
-        self.cancel_button_text = text;
+    /// Write the date to the selection as soon as it changes in the popup, e.g. with the combo
+    /// boxes, the arrows, the presets or the keyboard, instead of waiting for Save. The day is
+    /// clamped to the month.
+    /// Always on without [`Self::show_confirm_buttons`]. (Default: false)
+    /// Useful with [`Self::calendar`] off for a compact inline picker.
+    #[inline]
+    pub fn live_commit(mut self, live_commit: bool) -> Self {
+        self.config.live_commit = live_commit;
         self
     }
 
@@ -114,7 +711,15 @@ impl<'a> DatePickerButton<'a> {
     /// Must be set if multiple date picker buttons are in the same Ui.
     #[inline]
     pub fn id_salt(mut self, id_salt: &'a str) -> Self {
-        self.id_salt = Some(id_salt);
+        self.config.id_salt = Some(id_salt);
+        self
+    }
+
+    /// Set the id of the button, instead of deriving it from [`Self::id_salt`].
+    /// Unlike the salt it is used as is, so it must be unique, e.g. `Id::new(("due_date", row))`.
+    #[inline]
+    pub fn id(mut self, id: Id) -> Self {
+        self.config.id = Some(id);
         self
     }
 
@@ -129,154 +734,1152 @@ impl<'a> DatePickerButton<'a> {
     /// Show combo boxes in date picker popup. (Default: true)
     #[inline]
     pub fn combo_boxes(mut self, combo_boxes: bool) -> Self {
-        self.combo_boxes = combo_boxes;
+        self.config.combo_boxes = combo_boxes;
+        self
+    }
+
+    /// Replace the combo boxes with year and month buttons that open a year or month grid
+    /// in place of the calendar. (Default: false)
+    #[inline]
+    pub fn quick_nav(mut self, quick_nav: bool) -> Self {
+        self.config.quick_nav = quick_nav;
         self
     }
 
     /// Show arrows in date picker popup. (Default: true)
     #[inline]
     pub fn arrows(mut self, arrows: bool) -> Self {
-        self.arrows = arrows;
+        self.config.arrows = arrows;
         self
     }
 
     /// Show calendar in date picker popup. (Default: true)
     #[inline]
     pub fn calendar(mut self, calendar: bool) -> Self {
-        self.calendar = calendar;
+        self.config.calendar = calendar;
+        self
+    }
+
+    /// Pick just a month or a year instead of a day, see [`Precision`]. This hides the calendar
+    /// and the combo boxes and arrows that change the day. Unless changed, the format of the
+    /// button becomes %Y-%m or %Y. (Default: [`Precision::Day`])
+    #[inline]
+    pub fn precision(mut self, precision: Precision) -> Self {
+        self.config.precision = precision;
         self
     }
 
     /// Show calendar week in date picker popup. (Default: true)
     #[inline]
     pub fn calendar_week(mut self, week: bool) -> Self {
-        self.calendar_week = week;
+        self.config.calendar_week = week;
+        self
+    }
+
+    /// Give the calendar week column a fixed width, leaving more room for the days.
+    /// (Default: as wide as a day)
+    #[inline]
+    pub fn week_column_width(mut self, week_column_width: f32) -> Self {
+        self.config.week_column_width = week_column_width
+            .is_finite()
+            .then_some(week_column_width.max(0.0));
+        self
+    }
+
+    /// Show the row with the weekday names, and "Week" if [`Self::calendar_week`] is shown,
+    /// above the calendar. (Default: true)
+    #[inline]
+    pub fn show_weekday_header(mut self, show_weekday_header: bool) -> Self {
+        self.config.show_weekday_header = show_weekday_header;
+        self
+    }
+
+    /// Lay out the calendar from right to left, e.g. for Arabic or Hebrew. (Default: false)
+    /// This mirrors the weekday columns and puts earlier dates on the right of the arrows.
+    ///
+    /// This is not derived from the [`Layout`] of the parent `Ui`, as right-to-left layouts
+    /// are commonly used to right-align widgets in left-to-right apps.
+    #[inline]
+    pub fn right_to_left(mut self, right_to_left: bool) -> Self {
+        self.config.right_to_left = right_to_left;
+        self
+    }
+
+    /// Show `<` and `>` next to the button, which move the date to the previous or next day
+    /// that can be picked right away, without opening the popup, e.g. for toolbars. Clicking the
+    /// button still opens the popup. (Default: false)
+    #[inline]
+    pub fn stepper(mut self, stepper: bool) -> Self {
+        self.config.stepper = stepper;
+        self
+    }
+
+    /// How to number the calendar weeks. (Default: [`WeekNumbering::Iso`])
+    #[inline]
+    pub fn week_numbering(mut self, week_numbering: WeekNumbering) -> Self {
+        self.config.week_numbering = week_numbering;
+        self
+    }
+
+    /// Show the year a week counts in next to its number, e.g. "52'23" for the last ISO week
+    /// of 2023, which also covers January 1, 2024. The column may need to be wider, see
+    /// [`Self::week_column_width`]. (Default: false)
+    #[inline]
+    pub fn week_show_year(mut self, week_show_year: bool) -> Self {
+        self.config.week_show_year = week_show_year;
         self
     }
 
     /// Show the calendar icon on the button. (Default: true)
     #[inline]
     pub fn show_icon(mut self, show_icon: bool) -> Self {
-        self.show_icon = show_icon;
+        self.config.show_icon = show_icon;
         self
     }
 
-    /// Change the format shown on the button. (Default: %Y-%m-%d)
-    /// See [`chrono::format::strftime`] for valid formats.
+    /// The icon shown on the button instead of the date if [`Self::show_icon`] is set.
+    /// (Default: 📆)
     #[inline]
-    pub fn format(mut self, format: impl Into<String>) -> Self {
-        self.format = format.into();
+    pub fn icon(mut self, icon: impl Into<RichText>) -> Self {
+        self.config.icon = icon.into();
         self
     }
 
-    /// Highlight weekend days. (Default: true)
+    /// An image shown on the button instead of [`Self::icon`] if [`Self::show_icon`] is set,
+    /// e.g. a calendar icon of the app. Size it with [`Image::fit_to_exact_size`] if it is
+    /// larger than the button. (Default: none)
     #[inline]
-    pub fn highlight_weekends(mut self, highlight_weekends: bool) -> Self {
-        self.highlight_weekends = highlight_weekends;
+    pub fn icon_image(mut self, icon_image: impl Into<Image<'a>>) -> Self {
+        self.config.icon_image = Some(icon_image.into());
         self
     }
-}
 
-impl Widget for DatePickerButton<'_> {
-    fn ui(self, ui: &mut Ui) -> egui::Response {
-        let id = ui.make_persistent_id(self.id_salt);
-        let mut button_state = ui
-            .data_mut(|data| data.get_persisted::<DatePickerButtonState>(id))
-            .unwrap_or_default();
+    /// Change the format shown on the button. (Default: %Y-%m-%d)
+    /// See [`chrono::format::strftime`] for valid formats.
+    ///
+    /// The format is not checked: an invalid one, like `%Q` or a time specifier for a
+    /// date-only button, cannot be filled by chrono and the button shows the format itself
+    /// instead of the date. Use [`Self::try_format`] for formats that are not known up front.
+    #[inline]
+    pub fn format(mut self, format: impl Into<String>) -> Self {
+        self.config.format = Some(format.into());
+        self
+    }
 
-        let mut text = if self.show_icon {
-            RichText::new("📆")
+    /// Like [`Self::format`], but checks the format by formatting a sample date first.
+    pub fn try_format(self, format: impl Into<String>) -> Result<Self, FormatError> {
+        let format = format.into();
+        let sample = NaiveDate::from_ymd_opt(2024, 1, 8)
+            .and_then(|date| date.and_hms_opt(13, 45, 30))
+            .expect("Could not create NaiveDateTime");
+        let mut text = String::new();
+        let result = if self.config.with_time {
+            write!(text, "{}", sample.format(&format))
         } else {
-            RichText::new(format!("{}", self.selection.format(&self.format)))
+            write!(text, "{}", sample.date().format(&format))
         };
-
-        let visuals = ui.visuals().widgets.open;
-        if button_state.picker_visible {
-            text = text.color(visuals.text_color());
-        }
-        let mut button = Button::new(text);
-        if button_state.picker_visible {
-            button = button.fill(visuals.weak_bg_fill).stroke(visuals.bg_stroke);
-        }
-        let mut button_response = ui.add(button);
-        if button_response.clicked() {
-            button_state.picker_visible = true;
-            ui.data_mut(|data| data.insert_persisted(id, button_state.clone()));
+        match result {
+            Ok(()) => Ok(self.format(format)),
+            Err(_) => Err(FormatError { format }),
         }
+    }
 
-        if button_state.picker_visible {
-            let width = 333.0;
-            let mut pos = button_response.rect.left_bottom();
-            let width_with_padding = width
-                + ui.style().spacing.item_spacing.x
-                + ui.style().spacing.window_margin.leftf()
-                + ui.style().spacing.window_margin.rightf();
-            if pos.x + width_with_padding > ui.clip_rect().right() {
-                pos.x = button_response.rect.right() - width_with_padding;
-            }
+    /// Produce the text of the button from the selected date with a closure instead of
+    /// [`Self::format`], e.g. "Q1 2024" or "Week 14". Like the formatted date, the text is only
+    /// shown if [`Self::show_icon`] is not set. (Default: none)
+    #[inline]
+    pub fn label(mut self, label: impl Fn(NaiveDate) -> String + 'a) -> Self {
+        self.config.label = Some(Box::new(label));
+        self
+    }
 
-            // Check to make sure the calendar never is displayed out of window
-            pos.x = pos.x.max(ui.style().spacing.window_margin.leftf());
+    /// Highlight weekend days. (Default: true)
+    #[inline]
+    pub fn highlight_weekends(mut self, highlight_weekends: bool) -> Self {
+        self.config.highlight_weekends = highlight_weekends;
+        self
+    }
 
-            //TODO(elwerene): Better positioning
+    /// Custom painting on top of each day in the calendar. (Default: none)
+    /// The closure gets the date and the rect of its button and is called after the
+    /// button, the selection fill and the today marker are drawn, e.g. to paint event
+    /// markers with `ui.painter()`.
+    #[inline]
+    pub fn day_decorator(mut self, decorator: impl Fn(NaiveDate, &mut Ui, Rect) + 'a) -> Self {
+        self.config.day_decorator = Some(Box::new(decorator));
+        self
+    }
 
-            let InnerResponse {
-                inner: saved,
-                response: area_response,
-            } = Area::new(ui.make_persistent_id(self.id_salt))
-                .kind(egui::UiKind::Picker)
-                .order(Order::Foreground)
-                .fixed_pos(pos)
-                .show(ui.ctx(), |ui| {
-                    let frame = Frame::popup(ui.style());
-                    frame
-                        .show(ui, |ui| {
-                            ui.set_min_width(width);
-                            ui.set_max_width(width);
+    /// Draw the days of the calendar with a custom closure instead of the default buttons.
+    /// The closure gets the date and its [`DayState`]; clicking the returned response
+    /// picks the day. (Default: none)
+    #[inline]
+    pub fn day_ui(
+        mut self,
+        day_ui: impl Fn(&mut Ui, NaiveDate, DayState) -> Response + 'a,
+    ) -> Self {
+        self.config.day_ui = Some(Box::new(day_ui));
+        self
+    }
 
-                            DatePickerPopup {
-                                selection: self.selection,
-                                button_id: id,
-                                combo_boxes: self.combo_boxes,
-                                arrows: self.arrows,
-                                calendar: self.calendar,
-                                calendar_week: self.calendar_week,
-                                highlight_weekends: self.highlight_weekends,
-                                day_names: self.day_names,
-                                month_names: self.month_names,
-                                save_button_text: self.save_button_text,
-                                cancel_button_text: self.cancel_button_text,
-                            }
-                            .draw(ui)
-                        })
-                        .inner
-                });
+    /// Change how the number of a day is shown in the calendar, e.g. with Eastern Arabic
+    /// numerals. (Default: `|day| day.to_string()`)
+    #[inline]
+    pub fn day_number_format(mut self, day_number_format: impl Fn(u32) -> String + 'a) -> Self {
+        self.config.day_number_format = Some(Box::new(day_number_format));
+        self
+    }
 
-            if saved {
-                button_response.mark_changed();
-            }
+    /// Alignment of the day numbers within their cells. (Default: [`Align::Center`])
+    #[inline]
+    pub fn day_align(mut self, day_align: Align) -> Self {
+        self.config.day_align = day_align;
+        self
+    }
 
-            if !button_response.clicked()
-                && (ui.input(|i| i.key_pressed(Key::Escape)) || area_response.clicked_elsewhere())
-            {
-                button_state.picker_visible = false;
-                ui.data_mut(|data| data.insert_persisted(id, button_state));
-            }
-        }
+    /// Opacity of the day numbers of the previous and next month, from 0.0 to 1.0.
+    /// (Default: 0.5)
+    #[inline]
+    pub fn adjacent_day_opacity(mut self, adjacent_day_opacity: f32) -> Self {
+        self.config.adjacent_day_opacity = adjacent_day_opacity.clamp(0.0, 1.0);
+        self
+    }
 
-        button_response
+    /// Opacity of the day numbers of days that cannot be picked, from 0.0 to 1.0, on top of how
+    /// egui shows disabled widgets. (Default: 1.0)
+    #[inline]
+    pub fn disabled_day_opacity(mut self, disabled_day_opacity: f32) -> Self {
+        self.config.disabled_day_opacity = disabled_day_opacity.clamp(0.0, 1.0);
+        self
     }
-}
 
-#[derive(Clone, Debug, Default)]
-struct DatePickerPopupState {
-    year: i32,
-    month: u32,
-    day: u32,
-    setup: bool,
-}
+    /// How to mark today's date in the calendar. (Default: a circle)
+    #[inline]
+    pub fn today_marker(mut self, today_marker: TodayMarker) -> Self {
+        self.config.today_marker = today_marker;
+        self
+    }
+
+    /// How to mark the selected day in the calendar. (Default: [`SelectionStyle::Fill`])
+    #[inline]
+    pub fn selection_style(mut self, selection_style: SelectionStyle) -> Self {
+        self.config.selection_style = selection_style;
+        self
+    }
+
+    /// Mark a second day with a thin outline, e.g. a reference date to compare the selection
+    /// with. The outline is also drawn if the day is selected. (Default: none)
+    #[inline]
+    pub fn secondary_date(mut self, secondary_date: Option<NaiveDate>) -> Self {
+        self.config.secondary_date = secondary_date;
+        self
+    }
+
+    /// Give some days a background color of their own, e.g. to mark deadlines.
+    /// The closure returns the color for a date, or `None` to keep the default.
+    /// (Default: none)
+    ///
+    /// The background of a day is, in order of precedence: the selection color if the day is
+    /// selected (see [`Self::selection_style`]), the [`TodayMarker::Fill`] color for today,
+    /// the color returned here, the weekend color (see [`Self::highlight_weekends`]) and finally
+    /// the default background.
+    #[inline]
+    pub fn highlighted_dates(
+        mut self,
+        highlighted_dates: impl Fn(NaiveDate) -> Option<Color32> + 'a,
+    ) -> Self {
+        self.config.highlighted_dates = Some(Box::new(highlighted_dates));
+        self
+    }
+
+    /// Called with the year and month whenever the popup shows another month, e.g. after
+    /// the arrows or combo boxes were used, and when it opens. Nothing is saved by then.
+    #[inline]
+    pub fn on_month_changed(mut self, on_month_changed: impl FnMut(i32, u32) + 'a) -> Self {
+        self.config.on_month_changed = Some(RefCell::new(Box::new(on_month_changed)));
+        self
+    }
+
+    /// Show a column of preset buttons next to the calendar, e.g. [`DEFAULT_PRESETS`].
+    /// Clicking one selects the date it computes from today. (Default: none)
+    #[inline]
+    pub fn presets(mut self, presets: &'static [Preset]) -> Self {
+        self.config.presets = presets;
+        self
+    }
+
+    /// Height of the rows of the popup: the calendar weeks, its header, the navigation,
+    /// and the Save and Cancel buttons. Larger rows make for better touch targets.
+    /// Values below 10.0 (or not finite) are raised to 10.0. (Default: 20.0)
+    #[inline]
+    pub fn row_height(mut self, row_height: f32) -> Self {
+        self.config.row_height = if row_height.is_finite() {
+            row_height.max(MIN_ROW_HEIGHT)
+        } else {
+            MIN_ROW_HEIGHT
+        };
+        self
+    }
+
+    /// Space between the cells of the calendar. (Default: 1.0 by 2.0)
+    #[inline]
+    pub fn cell_spacing(mut self, cell_spacing: Vec2) -> Self {
+        self.config.cell_spacing = cell_spacing.max(Vec2::ZERO);
+        self
+    }
+
+    /// Space between the rows of the popup, and between the widgets within them.
+    /// (Default: 2.0)
+    #[inline]
+    pub fn popup_spacing(mut self, popup_spacing: f32) -> Self {
+        self.config.popup_spacing = popup_spacing.max(0.0);
+        self
+    }
+
+    /// Show the full date when hovering a day, see [`Self::hover_format`]. (Default: false)
+    #[inline]
+    pub fn day_hover_text(mut self, day_hover_text: bool) -> Self {
+        self.config.day_hover_text = day_hover_text;
+        self
+    }
+
+    /// Change the format of the date shown when hovering a day. (Default: %A, %B %-d, %Y)
+    /// See [`chrono::format::strftime`] for valid formats.
+    #[inline]
+    pub fn hover_format(mut self, hover_format: impl Into<String>) -> Self {
+        self.config.hover_format = hover_format.into();
+        self
+    }
+
+    /// Show the week number, according to the [`Self::week_numbering`], when hovering a day,
+    /// after the full date if [`Self::day_hover_text`] is set. Works without the
+    /// [`Self::calendar_week`] column. (Default: false)
+    #[inline]
+    pub fn week_number_on_hover(mut self, week_number_on_hover: bool) -> Self {
+        self.config.week_number_on_hover = week_number_on_hover;
+        self
+    }
+
+    /// Show the day of the year of the picked day below the calendar, e.g. "DOY 039" for
+    /// February 8th. (Default: false)
+    #[inline]
+    pub fn show_day_of_year(mut self, show_day_of_year: bool) -> Self {
+        self.config.show_day_of_year = show_day_of_year;
+        self
+    }
+
+    /// Highlight the whole row of the calendar while one of its days is hovered, for easier
+    /// scanning of dense calendars. (Default: false)
+    #[inline]
+    pub fn highlight_hovered_week(mut self, highlight_hovered_week: bool) -> Self {
+        self.config.highlight_hovered_week = highlight_hovered_week;
+        self
+    }
+
+    /// Change the frame around the popup, e.g. to match the styling of the app.
+    /// (Default: [`Frame::popup`])
+    #[inline]
+    pub fn frame(mut self, frame: Frame) -> Self {
+        self.config.frame = Some(frame);
+        self
+    }
+
+    /// Width of the date picker popup, without the preset column. (Default: 333.0)
+    /// Values below 150.0 (or not finite) are raised to 150.0, and the popup never gets wider
+    /// than the screen.
+    #[inline]
+    pub fn popup_width(mut self, popup_width: f32) -> Self {
+        self.config.popup_width = if popup_width.is_finite() {
+            popup_width.max(MIN_POPUP_WIDTH)
+        } else {
+            MIN_POPUP_WIDTH
+        };
+        self
+    }
+
+    /// Where to open the popup relative to the button. (Default: [`Placement::Auto`])
+    #[inline]
+    pub fn popup_placement(mut self, popup_placement: Placement) -> Self {
+        self.config.popup_placement = popup_placement;
+        self
+    }
+
+    /// Place the popup relative to this rect instead of the button, e.g. a table cell or the
+    /// response of a custom icon. The popup still closes when the button is scrolled out of
+    /// view. (Default: the rect of the button)
+    #[inline]
+    pub fn anchor(mut self, anchor: Rect) -> Self {
+        self.config.anchor = Some(anchor);
+        self
+    }
+
+    /// The layer the popup is shown on, e.g. [`Order::Tooltip`] to show it above other
+    /// foreground areas. (Default: [`Order::Foreground`])
+    /// The lists of combo boxes always open on the foreground layer, so above it the year and
+    /// month are picked with the [`Self::quick_nav`] grids instead of [`Self::combo_boxes`], and
+    /// the time with drag values.
+    #[inline]
+    pub fn popup_order(mut self, popup_order: Order) -> Self {
+        self.config.popup_order = popup_order;
+        self
+    }
+
+    /// The minimum gap between the popup and the edges of the screen. The popup is moved to
+    /// stay on the screen, and scrolls if it is taller than the screen.
+    /// (Default: the window margin of the style)
+    #[inline]
+    pub fn screen_margin(mut self, screen_margin: f32) -> Self {
+        self.config.screen_margin = Some(screen_margin);
+        self
+    }
+
+    /// Also pick seconds when picking a time, see [`Self::new_with_time`]. (Default: false)
+    #[inline]
+    pub fn show_seconds(mut self, show_seconds: bool) -> Self {
+        self.config.show_seconds = show_seconds;
+        self
+    }
+
+    /// Whether the button can be clicked. A disabled button still shows the date, but never
+    /// opens the popup, and closes it if it is open. (Default: true)
+    #[inline]
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.config.enabled = enabled;
+        self
+    }
+
+    /// Open the popup the first time the button is shown. (Default: false)
+    /// Use [`set_open`] to open or close it later on.
+    #[inline]
+    pub fn default_open(mut self, default_open: bool) -> Self {
+        self.config.default_open = default_open;
+        self
+    }
+
+    /// Open the popup on the given month instead of the month of the selection, e.g. next
+    /// month for scheduling a follow-up. Only the first frame of the popup is affected. Unless
+    /// it is the month of the selection, no day is shown as selected until the user picks one,
+    /// except with a [`Self::precision`] of a month or a year, which has no days to pick.
+    /// An invalid month is ignored. (Default: the month of the selection)
+    #[inline]
+    pub fn initial_view(mut self, year: i32, month: u32) -> Self {
+        self.config.initial_view = Some((year, month));
+        self
+    }
+
+    /// Reopen the popup on the month that was shown when it was last closed, instead of the
+    /// month of the selection. The selected day is still reset to the selection, and is only
+    /// shown as selected in its own month. Takes precedence over [`Self::initial_view`] once
+    /// the popup has been opened. (Default: false)
+    #[inline]
+    pub fn remember_view(mut self, remember_view: bool) -> Self {
+        self.config.remember_view = remember_view;
+        self
+    }
+
+    /// The earliest day that can be picked. Days before it are disabled. (Default: none)
+    ///
+    /// If the selection is out of range, e.g. a stored date from before the range was
+    /// tightened, the popup opens at the closest allowed day instead, see [`clamp_date`]. The
+    /// selection itself only changes once that day is saved.
+    #[inline]
+    pub fn min_date(mut self, min_date: NaiveDate) -> Self {
+        self.config.min_date = Some(min_date);
+        self
+    }
+
+    /// The latest day that can be picked. Days after it are disabled. (Default: none)
+    /// See [`Self::min_date`] for selections out of range.
+    #[inline]
+    pub fn max_date(mut self, max_date: NaiveDate) -> Self {
+        self.config.max_date = Some(max_date);
+        self
+    }
+
+    /// Disable the days before today, on top of [`Self::min_date`]. (Default: false)
+    #[inline]
+    pub fn disable_past(mut self, disable_past: bool) -> Self {
+        self.config.disable_past = disable_past;
+        self
+    }
+
+    /// Disable the days after today, on top of [`Self::max_date`]. (Default: false)
+    #[inline]
+    pub fn disable_future(mut self, disable_future: bool) -> Self {
+        self.config.disable_future = disable_future;
+        self
+    }
+
+    /// The weekdays that can be picked, e.g. Monday to Friday for business days only.
+    /// Days on other weekdays are disabled, but still highlighted as weekend. (Default: all)
+    #[inline]
+    pub fn selectable_weekdays(mut self, selectable_weekdays: &'a [Weekday]) -> Self {
+        self.config.selectable_weekdays = selectable_weekdays;
+        self
+    }
+
+    /// Make the week numbers buttons that pick the first day of their week which can be
+    /// picked, see [`Self::min_date`]. The week of the picked day is shown as selected.
+    /// (Default: false)
+    #[inline]
+    pub fn week_selectable(mut self, week_selectable: bool) -> Self {
+        self.config.week_selectable = week_selectable;
+        self
+    }
+
+    /// Fade the popup in and out. It only takes input once it is fully shown. (Default: false)
+    #[inline]
+    pub fn animate(mut self, animate: bool) -> Self {
+        self.config.animate = animate;
+        self
+    }
+
+    /// Parses a date in [`Self::format`], the inverse of [`Self::formatted`]. Same as the free
+    /// [`parse`] function, which also works while the button borrows the selection.
+    pub fn parse(&self, text: &str) -> Option<NaiveDate> {
+        parse(self.config.format(), text)
+    }
+}
+
+impl DatePickerButton<'_> {
+    /// The selection as the button shows it with [`Self::format`], e.g. to fill a text field
+    /// next to the button. Returns `None` if chrono cannot show the format. [`Self::label`]
+    /// and [`Self::show_icon`] are not taken into account.
+    pub fn formatted(&self) -> Option<String> {
+        let format = self.config.format();
+        let mut text = String::new();
+        let result = match &self.selection.0 {
+            Selection::Date(date) => write!(text, "{}", date.format(format)),
+            Selection::DateTime(date_time) => write!(text, "{}", date_time.format(format)),
+            Selection::Accessors { get, .. } => write!(text, "{}", get().format(format)),
+        };
+        result.ok().map(|()| text)
+    }
+
+    /// Shows the button and, if open, its popup.
+    ///
+    /// Same as adding the button with [`Ui::add`], but also tells whether the popup is open.
+    pub fn show(self, ui: &mut Ui) -> DatePickerResponse {
+        let WithSelection(selection) = self.selection;
+        self.config.show_selection(ui, selection)
+    }
+}
+
+impl ButtonConfig<'_> {
+    /// The format of the button, see [`DatePickerButton::format`]. Unless set, it fits the
+    /// precision.
+    fn format(&self) -> &str {
+        self.format.as_deref().unwrap_or(match self.precision {
+            _ if self.with_time => "%Y-%m-%d %H:%M",
+            Precision::Day => "%Y-%m-%d",
+            Precision::Month => "%Y-%m",
+            Precision::Year => "%Y",
+        })
+    }
+
+    fn show_selection(&self, ui: &mut Ui, selection: Selection<'_>) -> DatePickerResponse {
+        let id = self
+            .id
+            .unwrap_or_else(|| ui.make_persistent_id(self.id_salt));
+        // Store the initial state right away, so `is_open` and friends see it
+        let mut button_state = ui.data_mut(|data| {
+            data.get_persisted_mut_or_insert_with(id, || DatePickerButtonState {
+                picker_visible: self.default_open && self.enabled,
+            })
+            .clone()
+        });
+
+        let (mut date, mut time) = match &selection {
+            Selection::Date(date) => (**date, None),
+            Selection::DateTime(date_time) => (date_time.date(), Some(date_time.time())),
+            Selection::Accessors { get, .. } => (get(), None),
+        };
+        let initial_date = date;
+
+        let mut text = if self.show_icon {
+            self.icon.clone()
+        } else if let Some(label) = &self.label {
+            RichText::new(label(date))
+        } else {
+            let mut formatted = String::new();
+            let result = if let Some(time) = time {
+                write!(formatted, "{}", date.and_time(time).format(self.format()))
+            } else {
+                write!(formatted, "{}", date.format(self.format()))
+            };
+            // Show the format chrono cannot fill rather than panicking
+            if result.is_err() {
+                formatted = self.format().to_owned();
+            }
+            RichText::new(formatted)
+        };
+
+        let visuals = ui.visuals().widgets.open;
+        if button_state.picker_visible {
+            text = text.color(visuals.text_color());
+        }
+        let mut button = match &self.icon_image {
+            Some(image) if self.show_icon => Button::image(image.clone()),
+            _ => Button::new(text),
+        };
+        if button_state.picker_visible {
+            button = button.fill(visuals.weak_bg_fill).stroke(visuals.bg_stroke);
+        }
+        // The tighter of the explicit bounds and today
+        let today = chrono::offset::Utc::now().date_naive();
+        let min_date = self.min_date.max(self.disable_past.then_some(today));
+        let max_date = match (self.max_date, self.disable_future.then_some(today)) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+
+        let mut stepped = None;
+        let mut button_response = if self.stepper {
+            let (left, right) = if self.right_to_left {
+                (NavigationStep::NextDay, NavigationStep::PreviousDay)
+            } else {
+                (NavigationStep::PreviousDay, NavigationStep::NextDay)
+            };
+            ui.horizontal(|ui| {
+                let mut step_button = |ui: &mut Ui, glyph, step| {
+                    let target = self.step_target(date, step, min_date, max_date);
+                    if ui
+                        .add_enabled(self.enabled && target.is_some(), Button::new(glyph))
+                        .on_hover_text(step.label())
+                        .clicked()
+                    {
+                        stepped = target;
+                    }
+                };
+                step_button(ui, "<", left);
+                let button_response = ui.add_enabled(self.enabled, button);
+                step_button(ui, ">", right);
+                button_response
+            })
+            .inner
+        } else {
+            ui.add_enabled(self.enabled, button)
+        };
+        if button_response.clicked() {
+            button_state.picker_visible = true;
+            // Opened from the keyboard, so move the focus into the popup
+            let has_focus = button_response.has_focus();
+            ui.data_mut(|data| {
+                data.insert_persisted(id, button_state.clone());
+                if has_focus {
+                    data.insert_temp(focus_day_id(id), true);
+                }
+            });
+        }
+
+        // Close the popup when the button is disabled or scrolled out of view, instead of leaving
+        // it floating
+        let detached = button_state.picker_visible
+            && (!self.enabled || !ui.is_sizing_pass() && !ui.is_rect_visible(button_response.rect));
+        if detached {
+            button_state.picker_visible = false;
+            ui.data_mut(|data| {
+                data.insert_persisted(id, button_state.clone());
+                reset_popup(data, id);
+            });
+        }
+
+        let opacity = if self.animate {
+            ui.ctx()
+                .animate_bool_with_time(id.with("fade"), button_state.picker_visible, 0.15)
+        } else if button_state.picker_visible {
+            1.0
+        } else {
+            0.0
+        };
+
+        // Whether to write `date` and `time` to the selection
+        let mut save = false;
+        // Whether the popup had keyboard focus, which goes back to the button when it closes
+        let mut popup_had_focus = false;
+        if let Some(day) = stepped {
+            date = day;
+            save = true;
+        }
+
+        let mut outcome = if detached {
+            PopupOutcome::Cancelled
+        } else {
+            PopupOutcome::Closed
+        };
+
+        // While fading in or out the popup is shown, but does not take input
+        if opacity > 0.0 {
+            let interactive = button_state.picker_visible && opacity >= 1.0;
+            let mut width = self.popup_width;
+            if !self.presets.is_empty() {
+                width += PRESETS_WIDTH;
+            }
+            let screen_margin = self
+                .screen_margin
+                .unwrap_or_else(|| ui.style().spacing.window_margin.leftf());
+            let screen = ui.ctx().screen_rect().shrink(screen_margin.max(0.0));
+            width = width.min(
+                screen.width()
+                    - ui.style().spacing.window_margin.leftf()
+                    - ui.style().spacing.window_margin.rightf(),
+            );
+            width = width.max(MIN_POPUP_WIDTH);
+            let anchor = self.anchor.unwrap_or(button_response.rect);
+            let mut pos = anchor.left_bottom();
+            let width_with_padding = width
+                + ui.style().spacing.item_spacing.x
+                + ui.style().spacing.window_margin.leftf()
+                + ui.style().spacing.window_margin.rightf();
+            if pos.x + width_with_padding > ui.clip_rect().right() {
+                pos.x = anchor.right() - width_with_padding;
+            }
+
+            // Check to make sure the calendar never is displayed out of window
+            pos.x = pos
+                .x
+                .min(screen.right() - width_with_padding)
+                .max(screen.left());
+
+            // The popup is invisible during its first frame while egui measures it,
+            // so its size from the previous frame is good enough here.
+            let popup_height = ui
+                .ctx()
+                .memory(|mem| mem.area_rect(id))
+                .map_or(0.0, |rect| rect.height());
+            // Scroll if the popup does not fit on the screen
+            let scroll = popup_height >= screen.height();
+            let popup_height = popup_height.min(screen.height());
+            let above = match self.popup_placement {
+                Placement::Below => false,
+                Placement::Above => true,
+                Placement::Auto => {
+                    let room_below = ui.clip_rect().bottom() - anchor.bottom();
+                    let room_above = anchor.top() - ui.clip_rect().top();
+                    popup_height > room_below && room_above > room_below
+                }
+            };
+            let pivot = if above {
+                pos.y = anchor.top();
+                Align2::LEFT_BOTTOM
+            } else {
+                Align2::LEFT_TOP
+            };
+            // Move the popup onto the screen, even if it then covers the button
+            pos.y = if above {
+                pos.y.clamp(screen.top() + popup_height, screen.bottom())
+            } else {
+                pos.y.clamp(screen.top(), screen.bottom() - popup_height)
+            };
+
+            // Clicks into the list of an open combo box may land outside of the popup
+            let combo_box_open = ui.memory(|mem| mem.any_popup_open());
+
+            let shown_month = |ui: &Ui| {
+                ui.data_mut(|data| data.get_persisted::<DatePickerPopupState>(popup_id(id)))
+                    .filter(|state| state.setup)
+                    .map(|state| (state.year, state.month))
+            };
+            let month_before = shown_month(ui);
+
+            let InnerResponse {
+                inner: popup_outcome,
+                response: area_response,
+            } = Area::new(id)
+                .kind(egui::UiKind::Picker)
+                .order(self.popup_order)
+                .pivot(pivot)
+                .fixed_pos(pos)
+                .interactable(interactive)
+                .show(ui.ctx(), |ui| {
+                    ui.multiply_opacity(opacity);
+                    let frame = self.frame.unwrap_or_else(|| Frame::popup(ui.style()));
+                    let max_height = screen.height() - frame.total_margin().sum().y;
+                    frame
+                        .show(ui, |ui| {
+                            ui.set_min_width(width);
+                            ui.set_max_width(width);
+                            if !interactive {
+                                ui.disable();
+                            }
+
+                            let mut popup = self.popup(
+                                &mut date,
+                                time.as_mut(),
+                                id,
+                                interactive,
+                                (min_date, max_date),
+                            );
+                            if scroll {
+                                ScrollArea::vertical()
+                                    .max_height(max_height)
+                                    .show(ui, |ui| popup.draw(ui))
+                                    .inner
+                            } else {
+                                popup.draw(ui)
+                            }
+                        })
+                        .inner
+                });
+            area_response
+                .widget_info(|| WidgetInfo::labeled(WidgetType::Other, true, "Date picker"));
+
+            // Keep the keyboard focus within the popup once it is there
+            let focus_id = id.with("focus_in_popup");
+            let had_focus = ui.data_mut(|data| data.remove_temp::<bool>(focus_id)) == Some(true);
+            let focused = ui.memory(|mem| mem.focused());
+            let focus_in_popup = focused
+                .and_then(|focused| ui.ctx().read_response(focused))
+                .is_some_and(|focused| focused.layer_id == area_response.layer_id);
+            // While `Tab` wraps around no widget has the focus for a frame, unlike after `Escape`
+            let trap_focus = focus_in_popup
+                || had_focus && focused.is_none() && !ui.input(|i| i.key_pressed(Key::Escape));
+            if interactive && trap_focus {
+                ui.memory_mut(|mem| mem.set_modal_layer(area_response.layer_id));
+            }
+            // `Escape` takes the focus away before the popup closes
+            popup_had_focus = trap_focus || had_focus;
+            ui.data_mut(|data| data.insert_temp(focus_id, trap_focus));
+
+            if let Some(on_month_changed) = &self.on_month_changed {
+                let month_after = shown_month(ui);
+                if let Some((year, month)) = month_after.filter(|_| month_after != month_before) {
+                    (on_month_changed.borrow_mut())(year, month);
+                }
+            }
+
+            // While fading out the popup does not take input and has no outcome
+            if button_state.picker_visible {
+                outcome = popup_outcome;
+            }
+
+            if popup_outcome == PopupOutcome::Saved {
+                save = true;
+            }
+
+            if button_state.picker_visible
+                && !button_response.clicked()
+                && ((self.close_on_escape && ui.input(|i| i.key_pressed(Key::Escape)))
+                    || (self.close_on_click_outside
+                        && area_response.clicked_elsewhere()
+                        && !combo_box_open))
+            {
+                button_state.picker_visible = false;
+                ui.data_mut(|data| {
+                    data.insert_persisted(id, button_state.clone());
+                    reset_popup(data, id);
+                });
+                outcome = PopupOutcome::Cancelled;
+            }
+        }
+
+        if save {
+            // Saving the same value again does not count as a change
+            let changed = match selection {
+                Selection::Date(selection) => std::mem::replace(selection, date) != date,
+                Selection::DateTime(selection) => {
+                    let date_time = date.and_time(time.unwrap_or_default());
+                    std::mem::replace(selection, date_time) != date_time
+                }
+                Selection::Accessors { mut set, .. } => {
+                    set(date);
+                    initial_date != date
+                }
+            };
+            if changed {
+                button_response.mark_changed();
+            }
+        }
+
+        // The popup may have closed itself, e.g. when a day was saved
+        let is_open = ui
+            .data_mut(|data| data.get_persisted::<DatePickerButtonState>(id))
+            .map_or(button_state.picker_visible, |state| state.picker_visible);
+
+        if popup_had_focus && !is_open {
+            button_response.request_focus();
+        }
+        if !is_open {
+            ui.data_mut(|data| data.remove_temp::<bool>(focus_day_id(id)));
+        }
+
+        DatePickerResponse {
+            changed: button_response.changed(),
+            response: button_response,
+            is_open,
+            outcome,
+        }
+    }
+
+    /// The popup for `selection`, configured like the button.
+    fn popup<'b>(
+        &'b self,
+        selection: &'b mut NaiveDate,
+        time: Option<&'b mut NaiveTime>,
+        button_id: Id,
+        interactive: bool,
+        (min_date, max_date): (Option<NaiveDate>, Option<NaiveDate>),
+    ) -> DatePickerPopup<'b> {
+        // The lists of combo boxes would open behind a popup above the foreground.
+        let combo_lists = self.popup_order <= Order::Foreground;
+        DatePickerPopup {
+            selection,
+            time,
+            show_seconds: self.show_seconds,
+            button_id,
+            interactive,
+            combo_boxes: self.combo_boxes && combo_lists,
+            quick_nav: self.quick_nav || (self.combo_boxes && !combo_lists),
+            time_combo_boxes: combo_lists,
+            arrows: self.arrows,
+            calendar: self.calendar && self.precision == Precision::Day,
+            precision: self.precision,
+            calendar_week: self.calendar_week,
+            week_column_width: self.week_column_width,
+            week_numbering: self.week_numbering,
+            week_show_year: self.week_show_year,
+            right_to_left: self.right_to_left,
+            highlight_weekends: self.highlight_weekends,
+            day_names: self.day_names,
+            full_day_names: self.full_day_names,
+            weekday_label_style: self.weekday_label_style,
+            month_names: self.month_names,
+            save_button_text: &self.save_button_text,
+            cancel_button_text: &self.cancel_button_text,
+            show_confirm_buttons: self.show_confirm_buttons,
+            close_on_save: self.close_on_save,
+            live_commit: self.live_commit || !self.show_confirm_buttons,
+            day_decorator: self.day_decorator.as_deref(),
+            day_ui: self.day_ui.as_deref(),
+            day_number_format: self.day_number_format.as_deref(),
+            day_align: self.day_align,
+            adjacent_day_opacity: self.adjacent_day_opacity,
+            disabled_day_opacity: self.disabled_day_opacity,
+            today_marker: self.today_marker,
+            selection_style: self.selection_style,
+            secondary_date: self.secondary_date,
+            highlighted_dates: self.highlighted_dates.as_deref(),
+            presets: self.presets,
+            initial_view: self.initial_view,
+            remember_view: self.remember_view,
+            min_date,
+            max_date,
+            selectable_weekdays: self.selectable_weekdays,
+            week_selectable: self.week_selectable,
+            show_weekday_header: self.show_weekday_header,
+            row_height: self.row_height,
+            cell_spacing: self.cell_spacing,
+            popup_spacing: self.popup_spacing,
+            hover_format: self.day_hover_text.then_some(self.hover_format.as_str()),
+            week_number_on_hover: self.week_number_on_hover,
+            show_day_of_year: self.show_day_of_year,
+            highlight_hovered_week: self.highlight_hovered_week,
+        }
+    }
+
+    /// The day a [`Self::stepper`] arrow moves `date` to, skipping days of weekdays that cannot
+    /// be picked. Returns `None` if there is no such day within the week or the bounds.
+    fn step_target(
+        &self,
+        date: NaiveDate,
+        step: NavigationStep,
+        min_date: Option<NaiveDate>,
+        max_date: Option<NaiveDate>,
+    ) -> Option<NaiveDate> {
+        let mut state = DatePickerPopupState {
+            year: date.year(),
+            month: date.month(),
+            day: date.day(),
+            ..Default::default()
+        };
+        for _ in 0..7 {
+            if !state.navigate(step) {
+                return None;
+            }
+            let day = state.date()?;
+            if min_date.is_some_and(|min| day < min) || max_date.is_some_and(|max| day > max) {
+                return None;
+            }
+            if self.selectable_weekdays.contains(&day.weekday()) {
+                return Some(day);
+            }
+        }
+        None
+    }
+}
+
+impl Widget for DatePickerButton<'_> {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        self.show(ui).response
+    }
+}
+
+/// What [`DatePickerButton::show`] returns.
+#[derive(Clone, Debug)]
+pub struct DatePickerResponse {
+    /// The response of the button.
+    pub response: Response,
+
+    /// Whether the popup is open after this frame.
+    pub is_open: bool,
+
+    /// Whether a different date was saved this frame.
+    pub changed: bool,
+
+    /// What happened to the popup this frame.
+    pub outcome: PopupOutcome,
+}
+
+/// What happened to the popup of a [`DatePickerButton`] in a frame, see [`DatePickerResponse`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PopupOutcome {
+    /// The popup is not shown.
+    Closed,
+
+    /// The popup is shown and nothing was saved.
+    Open,
+
+    /// A date was saved. The popup is closed unless [`DatePickerButton::close_on_save`] is off
+    /// or a time is still to be picked.
+    Saved,
+
+    /// The popup was closed without saving, with Cancel, Escape or a click outside, or because
+    /// the button was disabled or scrolled out of view.
+    Cancelled,
+}
+
+/// Returned by [`DatePickerButton::try_format`] for a format chrono cannot show.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatError {
+    format: String,
+}
+
+impl FormatError {
+    /// The rejected format.
+    pub fn format(&self) -> &str {
+        &self.format
+    }
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid date format {:?}: it contains an unknown specifier or one the value cannot fill",
+            self.format
+        )
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+enum CalendarView {
+    #[default]
+    Days,
+    Months,
+    Years,
+}
+
+/// A column of the calendar table.
+#[derive(Clone, Copy)]
+enum CalendarColumn {
+    Week,
+
+    /// Index of the weekday, starting at Monday.
+    Day(usize),
+}
+
+/// What the navigation arrows do, from left to right.
+#[derive(Clone, Copy)]
+enum NavigationStep {
+    PreviousYear,
+    PreviousMonth,
+    PreviousDay,
+    NextDay,
+    NextMonth,
+    NextYear,
+}
+
+impl NavigationStep {
+    const ALL: [Self; 6] = [
+        Self::PreviousYear,
+        Self::PreviousMonth,
+        Self::PreviousDay,
+        Self::NextDay,
+        Self::NextMonth,
+        Self::NextYear,
+    ];
+
+    /// Describes the step, for the hover text and screen readers.
+    fn label(self) -> &'static str {
+        match self {
+            Self::PreviousYear => "Previous year",
+            Self::PreviousMonth => "Previous month",
+            Self::PreviousDay => "Previous day",
+            Self::NextDay => "Next day",
+            Self::NextMonth => "Next month",
+            Self::NextYear => "Next year",
+        }
+    }
+
+    /// Whether the step moves by a day, which picks the day it lands on.
+    fn is_day(self) -> bool {
+        matches!(self, Self::PreviousDay | Self::NextDay)
+    }
+
+    /// Whether the step moves to a later date.
+    fn is_forward(self) -> bool {
+        matches!(self, Self::NextDay | Self::NextMonth | Self::NextYear)
+    }
+
+    /// The unit the step moves by.
+    fn unit(self) -> Precision {
+        match self {
+            Self::PreviousYear | Self::NextYear => Precision::Year,
+            Self::PreviousMonth | Self::NextMonth => Precision::Month,
+            Self::PreviousDay | Self::NextDay => Precision::Day,
+        }
+    }
+}
+
+/// State of the popup of a [`DatePickerButton`], i.e. what it shows and what is picked but not
+/// saved yet. Stored persisted in `ui.data` next to the [`DatePickerButtonState`].
+///
+/// Use [`popup_snapshot`] to read it.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct DatePickerPopupState {
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    view: CalendarView,
+    /// How many pages the year grid is paged away from the one of the picked year.
+    years_page: i32,
+    setup: bool,
+    /// The popup opened on [`DatePickerButton::initial_view`] away from the selection, and no
+    /// day was picked since. No day is shown as selected and nothing can be saved until one is.
+    /// Never set with a [`Precision`] of a month or a year.
+    unpicked: bool,
+    /// The selection the popup was last seeded from or saved to.
+    last_seen: Option<NaiveDate>,
+    last_seen_time: Option<NaiveTime>,
+}
 
 impl DatePickerPopupState {
+    /// The picked date, with the day clamped to the last day of the month.
+    /// Returns `None` if the year or month is out of range.
+    fn date(&self) -> Option<NaiveDate> {
+        let first = NaiveDate::from_ymd_opt(self.year, self.month, 1)?;
+        (1..=self.day.clamp(1, 31))
+            .rev()
+            .find_map(|day| first.with_day(day))
+    }
+
     fn last_day_of_month(&self) -> u32 {
         let date: NaiveDate =
             NaiveDate::from_ymd_opt(self.year, self.month, 1).expect("Could not create NaiveDate");
@@ -286,399 +1889,1898 @@ impl DatePickerPopupState {
             .or_else(|| date.with_day(29).map(|_| 29))
             .unwrap_or(28)
     }
+
+    /// Moves the picked date by `step`, with the day clamped to the month. Returns `false`,
+    /// leaving the state as is, if that would leave the [`supported_years`].
+    fn navigate(&mut self, step: NavigationStep) -> bool {
+        let Some(date) = self.date() else {
+            return false;
+        };
+        let target = match step {
+            NavigationStep::PreviousYear => date.checked_sub_months(Months::new(12)),
+            NavigationStep::PreviousMonth => date.checked_sub_months(Months::new(1)),
+            NavigationStep::PreviousDay => date.pred_opt(),
+            NavigationStep::NextDay => date.succ_opt(),
+            NavigationStep::NextMonth => date.checked_add_months(Months::new(1)),
+            NavigationStep::NextYear => date.checked_add_months(Months::new(12)),
+        };
+        let Some(target) = target.filter(|target| supported_years().contains(&target.year()))
+        else {
+            return false;
+        };
+        self.year = target.year();
+        self.month = target.month();
+        self.day = target.day();
+        true
+    }
+}
+
+/// What the popup of a date picker button currently shows, see [`popup_snapshot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct PopupSnapshot {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl PopupSnapshot {
+    /// The year the popup shows.
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// The month the popup shows, starting at 1.
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    /// The day picked in the popup, which is not saved yet.
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+}
+
+/// Returns what the popup of the date picker button with the given `id` currently shows,
+/// or `None` if it is not open.
+///
+/// See [`set_open`] for how to get the `id`.
+pub fn popup_snapshot(ui: &Ui, id: Id) -> Option<PopupSnapshot> {
+    if !is_open(ui, id) {
+        return None;
+    }
+    ui.data_mut(|data| data.get_persisted::<DatePickerPopupState>(popup_id(id)))
+        .filter(|state| state.setup)
+        .map(|state| PopupSnapshot {
+            year: state.year,
+            month: state.month,
+            day: state.day,
+        })
+}
+
+/// The state and the combo boxes of the popup key off this id, so the popups of several
+/// buttons open at once do not interfere.
+fn popup_id(button_id: Id) -> Id {
+    button_id.with("date_picker")
+}
+
+/// Set while the selected day of a popup opened from the keyboard is still to be focused.
+fn focus_day_id(button_id: Id) -> Id {
+    popup_id(button_id).with("focus_day")
+}
+
+/// Makes the popup start over from the selection the next time it is opened.
+fn reset_popup(data: &mut IdTypeMap, button_id: Id) {
+    let popup_state =
+        data.get_persisted_mut_or_default::<DatePickerPopupState>(popup_id(button_id));
+    popup_state.setup = false;
+    popup_state.view = CalendarView::Days;
 }
 
 pub(crate) struct DatePickerPopup<'a> {
     pub selection: &'a mut NaiveDate,
+    pub time: Option<&'a mut NaiveTime>,
+    pub show_seconds: bool,
     pub button_id: Id,
+    /// `false` while fading in or out: the state is shown, but not stored.
+    pub interactive: bool,
     pub combo_boxes: bool,
+    pub quick_nav: bool,
+    /// Pick the time with combo boxes rather than drag values.
+    pub time_combo_boxes: bool,
     pub arrows: bool,
     pub calendar: bool,
+    pub precision: Precision,
     pub calendar_week: bool,
+    pub week_column_width: Option<f32>,
+    pub week_numbering: WeekNumbering,
+    pub week_show_year: bool,
+    pub right_to_left: bool,
     pub highlight_weekends: bool,
     pub day_names: &'static [&'static str],
+    pub full_day_names: &'static [&'static str],
+    pub weekday_label_style: WeekdayLabelStyle,
     pub month_names: &'static [&'static str],
-    pub save_button_text: &'static str,
-    pub cancel_button_text: &'static str,
+    pub save_button_text: &'a str,
+    pub cancel_button_text: &'a str,
+    pub show_confirm_buttons: bool,
+    pub close_on_save: bool,
+    pub live_commit: bool,
+    pub day_decorator: Option<&'a DayDecorator<'a>>,
+    pub day_ui: Option<&'a DayUi<'a>>,
+    pub day_number_format: Option<&'a DayNumberFormat<'a>>,
+    pub day_align: Align,
+    pub adjacent_day_opacity: f32,
+    pub disabled_day_opacity: f32,
+    pub today_marker: TodayMarker,
+    pub selection_style: SelectionStyle,
+    pub secondary_date: Option<NaiveDate>,
+    pub highlighted_dates: Option<&'a DayHighlight<'a>>,
+    pub presets: &'static [Preset],
+    pub initial_view: Option<(i32, u32)>,
+    pub remember_view: bool,
+    pub min_date: Option<NaiveDate>,
+    pub max_date: Option<NaiveDate>,
+    pub selectable_weekdays: &'a [Weekday],
+    pub week_selectable: bool,
+    pub show_weekday_header: bool,
+    pub row_height: f32,
+    pub cell_spacing: Vec2,
+    pub popup_spacing: f32,
+    pub hover_format: Option<&'a str>,
+    pub week_number_on_hover: bool,
+    pub show_day_of_year: bool,
+    pub highlight_hovered_week: bool,
 }
 
 impl DatePickerPopup<'_> {
-    /// Returns `true` if user pressed `Save` button.
-    pub fn draw(&mut self, ui: &mut Ui) -> bool {
-        let id = ui.make_persistent_id("date_picker");
+    /// The number of `week`, with its year if [`DatePickerButton::week_show_year`] is set.
+    fn week_label(&self, week: &Week) -> String {
+        if self.week_show_year {
+            format!("{}'{:02}", week.number, week.year.rem_euclid(100))
+        } else {
+            week.number.to_string()
+        }
+    }
+
+    /// The label of the `i`th weekday, starting with Monday, in the configured style.
+    fn weekday_label(&self, i: usize) -> String {
+        match self.weekday_label_style {
+            WeekdayLabelStyle::Short => self.day_names.get(i).copied().unwrap_or_default().into(),
+            WeekdayLabelStyle::Narrow => self
+                .day_names
+                .get(i)
+                .and_then(|name| name.chars().next())
+                .map(String::from)
+                .unwrap_or_default(),
+            WeekdayLabelStyle::Full => self
+                .full_day_names
+                .get(i)
+                .copied()
+                .unwrap_or_default()
+                .into(),
+        }
+    }
+
+    /// The columns of the calendar from left to right.
+    fn columns(&self) -> Vec<CalendarColumn> {
+        let mut columns: Vec<CalendarColumn> = self
+            .calendar_week
+            .then_some(CalendarColumn::Week)
+            .into_iter()
+            .chain((0..7).map(CalendarColumn::Day))
+            .collect();
+        if self.right_to_left {
+            columns.reverse();
+        }
+        columns
+    }
+
+    /// The label of `column` in the header row of the calendar.
+    fn header_label(&self, column: CalendarColumn) -> String {
+        match column {
+            CalendarColumn::Week => "Week".to_owned(),
+            //TODO(elwerene): Locale
+            CalendarColumn::Day(i) => self.weekday_label(i),
+        }
+    }
+
+    /// Whether any day of `year` lies within the min and max date, and the [`supported_years`].
+    fn is_year_in_range(&self, year: i32) -> bool {
+        supported_years().contains(&year)
+            && self.min_date.map_or(true, |min| year >= min.year())
+            && self.max_date.map_or(true, |max| year <= max.year())
+    }
+
+    /// Whether `day` lies within the min and max date and falls on a selectable weekday.
+    fn is_enabled(&self, day: NaiveDate) -> bool {
+        self.min_date.map_or(true, |min| day >= min)
+            && self.max_date.map_or(true, |max| day <= max)
+            && self.selectable_weekdays.contains(&day.weekday())
+    }
+
+    /// Returns [`PopupOutcome::Saved`] if the selection was changed, e.g. because the user
+    /// pressed `Save`, and [`PopupOutcome::Cancelled`] if the user pressed `Cancel`.
+    pub fn draw(&mut self, ui: &mut Ui) -> PopupOutcome {
+        let id = popup_id(self.button_id);
         let today = chrono::offset::Utc::now().date_naive();
         let mut popup_state = ui
             .data_mut(|data| data.get_persisted::<DatePickerPopupState>(id))
             .unwrap_or_default();
-        if !popup_state.setup {
-            popup_state.year = self.selection.year();
-            popup_state.month = self.selection.month();
-            popup_state.day = self.selection.day();
+        // Re-seed if the selection was changed from outside while the popup is open
+        let time = self.time.as_deref().copied();
+        let stale = !popup_state.setup
+            || popup_state.last_seen != Some(*self.selection)
+            || popup_state.last_seen_time != time;
+        // While fading out, keep showing the state the popup closed with
+        let closing = !self.interactive && !is_open(ui, self.button_id);
+        if stale && !(closing && popup_state.last_seen.is_some()) {
+            // The month shown when the popup was last closed, if it was opened before
+            let last_view = (self.remember_view && popup_state.last_seen.is_some())
+                .then_some((popup_state.year, popup_state.month));
+            // Start at the closest allowed day if the selection is out of range
+            let mut start = clamp_date(*self.selection, self.min_date, self.max_date);
+            let years = supported_years();
+            if start.year() < *years.start() {
+                start = NaiveDate::from_ymd_opt(*years.start(), 1, 1).unwrap_or(start);
+            } else if start.year() > *years.end() {
+                start = NaiveDate::from_ymd_opt(*years.end(), 12, 31).unwrap_or(start);
+            }
+            popup_state.year = start.year();
+            popup_state.month = start.month();
+            popup_state.day = start.day();
+            popup_state.unpicked = false;
+            if let Some((year, month)) = last_view
+                .or(self.initial_view)
+                .filter(|_| !popup_state.setup)
+            {
+                if supported_years().contains(&year) && (1..=12).contains(&month) {
+                    // Without a calendar there is no day to pick, so the day of the selection
+                    // stays picked
+                    popup_state.unpicked = self.precision == Precision::Day
+                        && (year, month) != (start.year(), start.month());
+                    popup_state.year = year;
+                    popup_state.month = month;
+                    popup_state.day = popup_state.day.min(popup_state.last_day_of_month());
+                }
+            }
+            popup_state.view = CalendarView::Days;
             popup_state.setup = true;
-            ui.data_mut(|data| data.insert_persisted(id, popup_state.clone()));
+            if let Some(time) = time {
+                popup_state.hour = time.hour();
+                popup_state.minute = time.minute();
+                popup_state.second = time.second();
+            }
+            popup_state.last_seen = Some(*self.selection);
+            popup_state.last_seen_time = time;
+            // While fading in, seed again once the popup takes input, so that frame counts as
+            // opening it, e.g. for `on_month_changed`
+            if self.interactive {
+                ui.data_mut(|data| data.insert_persisted(id, popup_state.clone()));
+            }
         }
 
-        let weeks = month_data(popup_state.year, popup_state.month);
         let (mut close, mut saved) = (false, false);
-        let height = 20.0;
-        let spacing = 2.0;
+
+        // Keyboard shortcuts, unless a text field takes the keys
+        if ui.is_enabled() && !ui.ctx().wants_keyboard_input() {
+            let (today_pressed, home_pressed, end_pressed) = ui.input_mut(|i| {
+                (
+                    i.consume_key(Modifiers::NONE, Key::T),
+                    i.consume_key(Modifiers::NONE, Key::Home),
+                    i.consume_key(Modifiers::NONE, Key::End),
+                )
+            });
+            if today_pressed {
+                popup_state.year = today.year();
+                popup_state.month = today.month();
+                popup_state.day = today.day();
+                popup_state.view = CalendarView::Days;
+            } else if home_pressed {
+                popup_state.day = 1;
+            } else if end_pressed {
+                popup_state.day = popup_state.last_day_of_month();
+            }
+            if today_pressed || home_pressed || end_pressed {
+                popup_state.unpicked = false;
+                if self.live_commit {
+                    saved = self.commit(&mut popup_state);
+                }
+                ui.data_mut(|data| data.insert_persisted(id, popup_state.clone()));
+            }
+        }
+
+        let weeks = ui.memory_mut(|mem| {
+            mem.caches.cache::<MonthDataCache>().get((
+                popup_state.year,
+                popup_state.month,
+                self.week_numbering,
+            ))
+        });
+        let height = self.row_height;
+        let spacing = self.popup_spacing;
         ui.spacing_mut().item_spacing = Vec2::splat(spacing);
+        // Let buttons and combo boxes fill the rows, so taller rows make larger touch targets
+        ui.spacing_mut().interact_size.y = height - spacing;
 
         ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend); // Don't wrap any text
 
-        StripBuilder::new(ui)
-            .clip(false)
-            .sizes(
-                Size::exact(height),
-                match (self.combo_boxes, self.arrows) {
-                    (true, true) => 2,
-                    (true, false) | (false, true) => 1,
-                    (false, false) => 0,
-                },
-            )
-            .sizes(
-                Size::exact((spacing + height) * (weeks.len() + 1) as f32),
-                self.calendar as usize,
-            )
-            .size(Size::exact(height))
-            .vertical(|mut strip| {
-                if self.combo_boxes {
-                    strip.strip(|builder| {
-                        builder.sizes(Size::remainder(), 3).horizontal(|mut strip| {
-                            strip.cell(|ui| {
-                                ComboBox::from_id_salt("date_picker_year")
-                                    .selected_text(popup_state.year.to_string())
-                                    .show_ui(ui, |ui| {
-                                        for year in today.year() - 100..today.year() + 10 {
-                                            if ui
-                                                .selectable_value(
-                                                    &mut popup_state.year,
-                                                    year,
-                                                    year.to_string(),
-                                                )
-                                                .changed()
-                                            {
-                                                popup_state.day = popup_state
-                                                    .day
-                                                    .min(popup_state.last_day_of_month());
-                                                ui.memory_mut(|mem| {
-                                                    mem.data
-                                                        .insert_persisted(id, popup_state.clone());
-                                                });
-                                            }
+        let columns = self.columns();
+
+        ui.horizontal_top(|ui| {
+            if !self.presets.is_empty() {
+                ui.vertical(|ui| {
+                    ui.set_width(PRESETS_WIDTH - spacing);
+                    for (label, preset) in self.presets {
+                        let date = preset(today);
+                        // Like the days of the calendar, presets outside the range are disabled
+                        let enabled = self.first_enabled_day(date, self.precision).is_some();
+                        if ui
+                            .add_enabled_ui(enabled, |ui| {
+                                ui.add_sized([PRESETS_WIDTH - spacing, height], Button::new(*label))
+                            })
+                            .inner
+                            .clicked()
+                        {
+                            popup_state.year = date.year();
+                            popup_state.month = date.month();
+                            popup_state.day = date.day();
+                            popup_state.unpicked = false;
+                            popup_state.view = CalendarView::Days;
+                            if self.live_commit {
+                                saved = self.commit(&mut popup_state);
+                            }
+                            ui.data_mut(|data| data.insert_persisted(id, popup_state.clone()));
+                        }
+                    }
+                });
+            }
+
+            // The strip lays out its cells relative to the parent layout, so it needs a
+            // vertical one next to the presets
+            ui.vertical(|ui| {
+                StripBuilder::new(ui)
+                    .clip(false)
+                    .sizes(
+                        Size::exact(height),
+                        match (self.combo_boxes || self.quick_nav, self.arrows) {
+                            (true, true) => 2,
+                            (true, false) | (false, true) => 1,
+                            (false, false) => 0,
+                        },
+                    )
+                    .sizes(
+                        Size::exact(
+                            (self.cell_spacing.y + height)
+                                * (weeks.len() + self.show_weekday_header as usize) as f32,
+                        ),
+                        (self.calendar || popup_state.view != CalendarView::Days) as usize,
+                    )
+                    .sizes(Size::exact(height), self.show_day_of_year as usize)
+                    .sizes(Size::exact(height), self.time.is_some() as usize)
+                    .sizes(Size::exact(height), self.show_confirm_buttons as usize)
+                    .vertical(|mut strip| {
+                        if self.quick_nav {
+                            let labels = if self.precision == Precision::Year {
+                                1
+                            } else {
+                                2
+                            };
+                            strip.strip(|builder| {
+                                builder
+                                    .sizes(Size::remainder(), labels)
+                                    .horizontal(|mut strip| {
+                                        strip.cell(|ui| {
+                                            ui.with_layout(
+                                                Layout::top_down_justified(Align::Center),
+                                                |ui| {
+                                                    if ui
+                                                        .selectable_label(
+                                                            popup_state.view == CalendarView::Years,
+                                                            popup_state.year.to_string(),
+                                                        )
+                                                        .clicked()
+                                                    {
+                                                        popup_state.view = match popup_state.view {
+                                                            CalendarView::Years => {
+                                                                CalendarView::Days
+                                                            }
+                                                            _ => CalendarView::Years,
+                                                        };
+                                                        popup_state.years_page = 0;
+                                                        ui.data_mut(|data| {
+                                                            data.insert_persisted(
+                                                                id,
+                                                                popup_state.clone(),
+                                                            );
+                                                        });
+                                                    }
+                                                },
+                                            );
+                                        });
+                                        if labels < 2 {
+                                            return;
                                         }
+                                        strip.cell(|ui| {
+                                            ui.with_layout(
+                                                Layout::top_down_justified(Align::Center),
+                                                |ui| {
+                                                    if ui
+                                                        .selectable_label(
+                                                            popup_state.view
+                                                                == CalendarView::Months,
+                                                            month_name(
+                                                                popup_state.month as usize,
+                                                                self.month_names,
+                                                            ),
+                                                        )
+                                                        .clicked()
+                                                    {
+                                                        popup_state.view = match popup_state.view {
+                                                            CalendarView::Months => {
+                                                                CalendarView::Days
+                                                            }
+                                                            _ => CalendarView::Months,
+                                                        };
+                                                        ui.data_mut(|data| {
+                                                            data.insert_persisted(
+                                                                id,
+                                                                popup_state.clone(),
+                                                            );
+                                                        });
+                                                    }
+                                                },
+                                            );
+                                        });
                                     });
                             });
-                            strip.cell(|ui| {
-                                ComboBox::from_id_salt("date_picker_month")
-                                    .selected_text(month_name(
-                                        popup_state.month as usize,
-                                        self.month_names,
-                                    ))
-                                    .show_ui(ui, |ui| {
-                                        for month in 1..=12 {
-                                            if ui
-                                                .selectable_value(
-                                                    &mut popup_state.month,
-                                                    month,
-                                                    month_name(month as usize, self.month_names),
-                                                )
-                                                .changed()
+                        } else if self.combo_boxes {
+                            let combo_boxes = match self.precision {
+                                Precision::Day => 3,
+                                Precision::Month => 2,
+                                Precision::Year => 1,
+                            };
+                            strip.strip(|builder| {
+                                builder.sizes(Size::remainder(), combo_boxes).horizontal(
+                                    |mut strip| {
+                                        strip.cell(|ui| {
+                                            let years = today.year() - 100..today.year() + 10;
+                                            let response = ComboBox::from_id_salt(id.with("year"))
+                                                .width(ui.available_width())
+                                                .selected_text(popup_state.year.to_string())
+                                                .show_ui(ui, |ui| {
+                                                    let opened = combo_list_opened(ui);
+                                                    for year in years.clone() {
+                                                        if !self.is_year_in_range(year) {
+                                                            continue;
+                                                        }
+                                                        let selected = popup_state.year == year;
+                                                        let response = ui.selectable_value(
+                                                            &mut popup_state.year,
+                                                            year,
+                                                            year.to_string(),
+                                                        );
+                                                        if opened && selected {
+                                                            response.scroll_to_me_animation(
+                                                                Some(Align::Center),
+                                                                ScrollAnimation::none(),
+                                                            );
+                                                        }
+                                                        if response.changed() {
+                                                            saved = self.combo_box_changed(
+                                                                ui,
+                                                                &mut popup_state,
+                                                                id,
+                                                            );
+                                                        }
+                                                    }
+                                                })
+                                                .response;
+                                            let year =
+                                                popup_state.year + combo_box_step(ui, &response);
+                                            if year != popup_state.year
+                                                && years.contains(&year)
+                                                && self.is_year_in_range(year)
                                             {
-                                                popup_state.day = popup_state
-                                                    .day
-                                                    .min(popup_state.last_day_of_month());
-                                                ui.memory_mut(|mem| {
-                                                    mem.data
-                                                        .insert_persisted(id, popup_state.clone());
-                                                });
+                                                popup_state.year = year;
+                                                saved = self.combo_box_changed(
+                                                    ui,
+                                                    &mut popup_state,
+                                                    id,
+                                                );
                                             }
+                                        });
+                                        if combo_boxes < 2 {
+                                            return;
                                         }
-                                    });
-                            });
-                            strip.cell(|ui| {
-                                ComboBox::from_id_salt("date_picker_day")
-                                    .selected_text(popup_state.day.to_string())
-                                    .show_ui(ui, |ui| {
-                                        for day in 1..=popup_state.last_day_of_month() {
-                                            if ui
-                                                .selectable_value(
-                                                    &mut popup_state.day,
-                                                    day,
-                                                    day.to_string(),
-                                                )
-                                                .changed()
+                                        strip.cell(|ui| {
+                                            let response = ComboBox::from_id_salt(id.with("month"))
+                                                .width(ui.available_width())
+                                                .selected_text(month_name(
+                                                    popup_state.month as usize,
+                                                    self.month_names,
+                                                ))
+                                                .show_ui(ui, |ui| {
+                                                    let opened = combo_list_opened(ui);
+                                                    for month in 1..=12 {
+                                                        let selected = popup_state.month == month;
+                                                        let response = ui.selectable_value(
+                                                            &mut popup_state.month,
+                                                            month,
+                                                            month_name(
+                                                                month as usize,
+                                                                self.month_names,
+                                                            ),
+                                                        );
+                                                        if opened && selected {
+                                                            response.scroll_to_me_animation(
+                                                                Some(Align::Center),
+                                                                ScrollAnimation::none(),
+                                                            );
+                                                        }
+                                                        if response.changed() {
+                                                            saved = self.combo_box_changed(
+                                                                ui,
+                                                                &mut popup_state,
+                                                                id,
+                                                            );
+                                                        }
+                                                    }
+                                                })
+                                                .response;
+                                            let month = popup_state.month as i32
+                                                + combo_box_step(ui, &response);
+                                            if month != popup_state.month as i32
+                                                && (1..=12).contains(&month)
                                             {
-                                                ui.memory_mut(|mem| {
-                                                    mem.data
-                                                        .insert_persisted(id, popup_state.clone());
-                                                });
+                                                popup_state.month = month as u32;
+                                                saved = self.combo_box_changed(
+                                                    ui,
+                                                    &mut popup_state,
+                                                    id,
+                                                );
                                             }
+                                        });
+                                        if combo_boxes < 3 {
+                                            return;
                                         }
-                                    });
+                                        strip.cell(|ui| {
+                                            let response = ComboBox::from_id_salt(id.with("day"))
+                                                .width(ui.available_width())
+                                                .selected_text(popup_state.day.to_string())
+                                                .show_ui(ui, |ui| {
+                                                    let opened = combo_list_opened(ui);
+                                                    for day in 1..=popup_state.last_day_of_month() {
+                                                        let selected = popup_state.day == day;
+                                                        let response = ui.selectable_value(
+                                                            &mut popup_state.day,
+                                                            day,
+                                                            day.to_string(),
+                                                        );
+                                                        if opened && selected {
+                                                            response.scroll_to_me_animation(
+                                                                Some(Align::Center),
+                                                                ScrollAnimation::none(),
+                                                            );
+                                                        }
+                                                        // Picking the shown day picks it too
+                                                        if response.changed()
+                                                            || response.clicked()
+                                                                && popup_state.unpicked
+                                                        {
+                                                            popup_state.unpicked = false;
+                                                            saved = self.combo_box_changed(
+                                                                ui,
+                                                                &mut popup_state,
+                                                                id,
+                                                            );
+                                                        }
+                                                    }
+                                                })
+                                                .response;
+                                            let day = popup_state.day as i32
+                                                + combo_box_step(ui, &response);
+                                            if day != popup_state.day as i32
+                                                && (1..=popup_state.last_day_of_month() as i32)
+                                                    .contains(&day)
+                                            {
+                                                popup_state.day = day as u32;
+                                                popup_state.unpicked = false;
+                                                saved = self.combo_box_changed(
+                                                    ui,
+                                                    &mut popup_state,
+                                                    id,
+                                                );
+                                            }
+                                        });
+                                    },
+                                );
                             });
-                        });
-                    });
-                }
+                        }
 
-                if self.arrows {
-                    strip.strip(|builder| {
-                        builder.sizes(Size::remainder(), 6).horizontal(|mut strip| {
-                            strip.cell(|ui| {
-                                ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
-                                    if ui.button("<<<").on_hover_text(">>>").clicked() {
-                                        popup_state.year -= 1;
-                                        popup_state.day =
-                                            popup_state.day.min(popup_state.last_day_of_month());
-                                        ui.data_mut(|data| {
-                                            data.insert_persisted(id, popup_state.clone());
-                                        });
-                                    }
-                                });
+                        if self.arrows {
+                            let mut steps: Vec<_> = NavigationStep::ALL
+                                .into_iter()
+                                .filter(|&step| self.precision.allows(step))
+                                .collect();
+                            if self.right_to_left {
+                                // The arrows keep pointing the same way, but earlier dates are on
+                                // the right
+                                steps.reverse();
+                            }
+                            let glyphs = ["<<<", "<<", "<", ">", ">>", ">>>"]
+                                .into_iter()
+                                .zip(NavigationStep::ALL)
+                                .filter(|&(_, step)| self.precision.allows(step))
+                                .map(|(glyph, _)| glyph)
+                                .collect::<Vec<_>>();
+                            let count = steps.len();
+                            strip.strip(|builder| {
+                                builder
+                                    .sizes(Size::remainder(), count)
+                                    .horizontal(|mut strip| {
+                                        for (glyph, step) in glyphs.into_iter().zip(steps) {
+                                            strip.cell(|ui| {
+                                                ui.with_layout(
+                                                    Layout::top_down_justified(Align::Center),
+                                                    |ui| {
+                                                        let target =
+                                                            self.arrow_target(&popup_state, step);
+                                                        let enabled = target.is_some();
+                                                        let response = ui
+                                                            .add_enabled(
+                                                                enabled,
+                                                                Button::new(glyph),
+                                                            )
+                                                            .on_hover_text(step.label());
+                                                        response.widget_info(|| {
+                                                            WidgetInfo::labeled(
+                                                                WidgetType::Button,
+                                                                enabled,
+                                                                step.label(),
+                                                            )
+                                                        });
+                                                        if let Some(target) =
+                                                            target.filter(|_| response.clicked())
+                                                        {
+                                                            popup_state = target;
+                                                            popup_state.unpicked &= !step.is_day();
+                                                            if self.live_commit {
+                                                                saved =
+                                                                    self.commit(&mut popup_state);
+                                                            }
+                                                            ui.data_mut(|data| {
+                                                                data.insert_persisted(
+                                                                    id,
+                                                                    popup_state.clone(),
+                                                                );
+                                                            });
+                                                        }
+                                                    },
+                                                );
+                                            });
+                                        }
+                                    });
                             });
+                        }
+
+                        if popup_state.view != CalendarView::Days {
                             strip.cell(|ui| {
-                                ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
-                                    if ui.button("<<").on_hover_text("<<").clicked() {
-                                        popup_state.month -= 1;
-                                        if popup_state.month == 0 {
-                                            popup_state.month = 12;
-                                            popup_state.year -= 1;
-                                        }
-                                        popup_state.day =
-                                            popup_state.day.min(popup_state.last_day_of_month());
-                                        ui.data_mut(|data| {
-                                            data.insert_persisted(id, popup_state.clone());
-                                        });
-                                    }
-                                });
+                                if self.quick_nav_grid(ui, &mut popup_state, id) {
+                                    saved = true;
+                                }
                             });
+                        } else if self.calendar {
                             strip.cell(|ui| {
-                                ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
-                                    if ui.button("<").on_hover_text("<").clicked() {
-                                        popup_state.day -= 1;
-                                        if popup_state.day == 0 {
-                                            popup_state.month -= 1;
-                                            if popup_state.month == 0 {
-                                                popup_state.year -= 1;
-                                                popup_state.month = 12;
+                                ui.spacing_mut().item_spacing = self.cell_spacing;
+                                let mut picked = None;
+                                // The row hovered in the previous frame, by its Monday
+                                let hovered_week_id = id.with("hovered_week");
+                                let hovered_week = ui
+                                    .data_mut(|data| data.remove_temp::<NaiveDate>(hovered_week_id))
+                                    .filter(|_| self.highlight_hovered_week);
+                                let add_body = |mut body: TableBody<'_>| {
+                                    for week in weeks.iter() {
+                                        let week_hovered = hovered_week == Some(week.days[0]);
+                                        body.row(height, |mut row| {
+                                            for &column in &columns {
+                                                let day = match column {
+                                                    CalendarColumn::Week => {
+                                                        row.col(|ui| {
+                                                            self.hover_week_cell(
+                                                                ui,
+                                                                hovered_week_id,
+                                                                week,
+                                                                week_hovered,
+                                                            );
+                                                            if !self.week_selectable {
+                                                                ui.label(self.week_label(week));
+                                                                return;
+                                                            }
+                                                            let first_day =
+                                                                week.days.iter().copied().find(
+                                                                    |&day| self.is_enabled(day),
+                                                                );
+                                                            // Mark the week of the picked day
+                                                            let selected = !popup_state.unpicked
+                                                                && popup_state.date().is_some_and(
+                                                                    |date| {
+                                                                        week.days.contains(&date)
+                                                                    },
+                                                                );
+                                                            ui.with_layout(
+                                                                Layout::top_down_justified(
+                                                                    Align::Center,
+                                                                ),
+                                                                |ui| {
+                                                                    if ui
+                                                                        .add_enabled(
+                                                                            first_day.is_some(),
+                                                                            Button::new(
+                                                                                self.week_label(
+                                                                                    week,
+                                                                                ),
+                                                                            )
+                                                                            .selected(selected),
+                                                                        )
+                                                                        .clicked()
+                                                                    {
+                                                                        picked = first_day;
+                                                                    }
+                                                                },
+                                                            );
+                                                        });
+                                                        continue;
+                                                    }
+                                                    CalendarColumn::Day(i) => week.days[i],
+                                                };
+                                                row.col(|ui| {
+                                                    self.hover_week_cell(
+                                                        ui,
+                                                        hovered_week_id,
+                                                        week,
+                                                        week_hovered,
+                                                    );
+                                                    ui.with_layout(
+                                                        Layout::top_down_justified(self.day_align),
+                                                        |ui| {
+                                                            let button_response = self.day_button(
+                                                                ui,
+                                                                day,
+                                                                &popup_state,
+                                                                today,
+                                                                height,
+                                                                week_hovered,
+                                                            );
+
+                                                            // A custom `day_ui` may not disable the button itself
+                                                            if button_response.clicked()
+                                                                && self.is_enabled(day)
+                                                            {
+                                                                picked = Some(day);
+                                                            }
+                                                        },
+                                                    );
+                                                });
                                             }
-                                            popup_state.day = popup_state.last_day_of_month();
-                                        }
-                                        ui.data_mut(|data| {
-                                            data.insert_persisted(id, popup_state.clone());
                                         });
                                     }
-                                });
-                            });
-                            strip.cell(|ui| {
-                                ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
-                                    if ui.button(">").on_hover_text(">").clicked() {
-                                        popup_state.day += 1;
-                                        if popup_state.day > popup_state.last_day_of_month() {
-                                            popup_state.day = 1;
-                                            popup_state.month += 1;
-                                            if popup_state.month > 12 {
-                                                popup_state.month = 1;
-                                                popup_state.year += 1;
+                                };
+                                let mut table = TableBuilder::new(ui).vscroll(false);
+                                for &column in &columns {
+                                    table = table.column(match (column, self.week_column_width) {
+                                        (CalendarColumn::Week, Some(width)) => Column::exact(width),
+                                        _ => Column::remainder(),
+                                    });
+                                }
+                                if self.show_weekday_header {
+                                    table
+                                        .header(height, |mut header| {
+                                            for &column in &columns {
+                                                header.col(|ui| {
+                                                    ui.with_layout(
+                                                        Layout::centered_and_justified(
+                                                            Direction::TopDown,
+                                                        ),
+                                                        |ui| {
+                                                            ui.label(self.header_label(column));
+                                                        },
+                                                    );
+                                                });
                                             }
-                                        }
-                                        ui.data_mut(|data| {
-                                            data.insert_persisted(id, popup_state.clone());
-                                        });
+                                        })
+                                        .body(add_body);
+                                } else {
+                                    table.body(add_body);
+                                }
+
+                                if let Some(day) = picked {
+                                    popup_state.year = day.year();
+                                    popup_state.month = day.month();
+                                    popup_state.day = day.day();
+                                    popup_state.unpicked = false;
+                                    if !self.show_confirm_buttons {
+                                        saved = self.commit(&mut popup_state);
+                                        // Keep the popup open to pick the time
+                                        close = saved && self.close_on_save && self.time.is_none();
                                     }
-                                });
+                                    ui.data_mut(|data| {
+                                        data.insert_persisted(id, popup_state.clone());
+                                    });
+                                }
                             });
+                        }
+
+                        if self.show_day_of_year {
                             strip.cell(|ui| {
-                                ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
-                                    if ui.button(">>").on_hover_text(">>").clicked() {
-                                        popup_state.month += 1;
-                                        if popup_state.month > 12 {
-                                            popup_state.month = 1;
-                                            popup_state.year += 1;
-                                        }
-                                        popup_state.day =
-                                            popup_state.day.min(popup_state.last_day_of_month());
-                                        ui.data_mut(|data| {
-                                            data.insert_persisted(id, popup_state.clone());
-                                        });
+                                ui.centered_and_justified(|ui| {
+                                    if let Some(date) =
+                                        popup_state.date().filter(|_| !popup_state.unpicked)
+                                    {
+                                        ui.weak(format!("DOY {:03}", date.ordinal()));
                                     }
                                 });
                             });
-                            strip.cell(|ui| {
-                                ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
-                                    if ui.button(">>>").on_hover_text(">>>").clicked() {
-                                        popup_state.year += 1;
-                                        popup_state.day =
-                                            popup_state.day.min(popup_state.last_day_of_month());
-                                        ui.data_mut(|data| {
-                                            data.insert_persisted(id, popup_state.clone());
-                                        });
-                                    }
-                                });
+                        }
+
+                        if self.time.is_some() {
+                            strip.strip(|builder| {
+                                let components = if self.show_seconds { 3 } else { 2 };
+                                builder.sizes(Size::remainder(), components).horizontal(
+                                    |mut strip| {
+                                        for component in 0..components {
+                                            strip.cell(|ui| {
+                                                let (id_salt, value, count) = match component {
+                                                    0 => ("hour", &mut popup_state.hour, 24),
+                                                    1 => ("minute", &mut popup_state.minute, 60),
+                                                    _ => ("second", &mut popup_state.second, 60),
+                                                };
+                                                let changed = if self.time_combo_boxes {
+                                                    time_combo_box(
+                                                        ui,
+                                                        id.with(id_salt),
+                                                        value,
+                                                        count,
+                                                    )
+                                                } else {
+                                                    time_drag_value(ui, value, count)
+                                                };
+                                                if changed {
+                                                    if !self.show_confirm_buttons {
+                                                        saved = self.commit(&mut popup_state);
+                                                    }
+                                                    ui.data_mut(|data| {
+                                                        data.insert_persisted(
+                                                            id,
+                                                            popup_state.clone(),
+                                                        );
+                                                    });
+                                                }
+                                            });
+                                        }
+                                    },
+                                );
                             });
-                        });
-                    });
-                }
+                        }
 
-                if self.calendar {
-                    strip.cell(|ui| {
-                        ui.spacing_mut().item_spacing = Vec2::new(1.0, 2.0);
-                        TableBuilder::new(ui)
-                            .vscroll(false)
-                            .columns(Column::remainder(), if self.calendar_week { 8 } else { 7 })
-                            .header(height, |mut header| {
-                                if self.calendar_week {
-                                    header.col(|ui| {
+                        if self.show_confirm_buttons {
+                            strip.strip(|builder| {
+                                builder.sizes(Size::remainder(), 3).horizontal(|mut strip| {
+                                    strip.empty();
+                                    strip.cell(|ui| {
                                         ui.with_layout(
-                                            Layout::centered_and_justified(Direction::TopDown),
+                                            Layout::top_down_justified(Align::Center),
                                             |ui| {
-                                                ui.label("Week");
+                                                if ui.button(self.cancel_button_text).clicked() {
+                                                    close = true;
+                                                }
                                             },
                                         );
                                     });
-                                }
-
-                                //TODO(elwerene): Locale
-                                for name in self.day_names {
-                                    header.col(|ui| {
+                                    strip.cell(|ui| {
                                         ui.with_layout(
-                                            Layout::centered_and_justified(Direction::TopDown),
+                                            Layout::top_down_justified(Align::Center),
                                             |ui| {
-                                                ui.label(*name);
+                                                let can_save = self.can_save(&popup_state);
+                                                if ui
+                                                    .add_enabled(
+                                                        can_save,
+                                                        Button::new(self.save_button_text),
+                                                    )
+                                                    .clicked()
+                                                {
+                                                    saved = self.commit(&mut popup_state);
+                                                    close = saved && self.close_on_save;
+                                                    ui.data_mut(|data| {
+                                                        data.insert_persisted(
+                                                            id,
+                                                            popup_state.clone(),
+                                                        );
+                                                    });
+                                                }
                                             },
                                         );
                                     });
-                                }
-                            })
-                            .body(|mut body| {
-                                for week in weeks {
-                                    body.row(height, |mut row| {
-                                        if self.calendar_week {
-                                            row.col(|ui| {
-                                                ui.label(week.number.to_string());
-                                            });
-                                        }
-                                        for day in week.days {
-                                            row.col(|ui| {
-                                                ui.with_layout(
-                                                    Layout::top_down_justified(Align::Center),
-                                                    |ui| {
-                                                        let fill_color = if popup_state.year
-                                                            == day.year()
-                                                            && popup_state.month == day.month()
-                                                            && popup_state.day == day.day()
-                                                        {
-                                                            ui.visuals().selection.bg_fill
-                                                        } else if (day.weekday() == Weekday::Sat
-                                                            || day.weekday() == Weekday::Sun)
-                                                            && self.highlight_weekends
-                                                        {
-                                                            if ui.visuals().dark_mode {
-                                                                Color32::DARK_RED
-                                                            } else {
-                                                                Color32::LIGHT_RED
-                                                            }
-                                                        } else {
-                                                            ui.visuals().extreme_bg_color
-                                                        };
+                                });
+                            });
+                        }
+                    });
+            });
+        });
 
-                                                        let mut text_color = ui
-                                                            .visuals()
-                                                            .widgets
-                                                            .inactive
-                                                            .text_color();
+        if close {
+            ui.data_mut(|data| {
+                data.insert_persisted(id, popup_state);
+                reset_popup(data, self.button_id);
+                data.get_persisted_mut_or_default::<DatePickerButtonState>(self.button_id)
+                    .picker_visible = false;
+            });
+        }
 
-                                                        if day.month() != popup_state.month {
-                                                            text_color =
-                                                                text_color.linear_multiply(0.5);
-                                                        };
+        if saved {
+            PopupOutcome::Saved
+        } else if close {
+            PopupOutcome::Cancelled
+        } else {
+            PopupOutcome::Open
+        }
+    }
 
-                                                        let button_response = ui.add(
-                                                            Button::new(
-                                                                RichText::new(
-                                                                    day.day().to_string(),
-                                                                )
-                                                                .color(text_color),
-                                                            )
-                                                            .fill(fill_color),
-                                                        );
+    /// Clamps the day to the month after a year, month or day combo box changed, and saves the
+    /// date right away with [`DatePickerButton::live_commit`]. Returns whether it was saved.
+    fn combo_box_changed(
+        &mut self,
+        ui: &Ui,
+        popup_state: &mut DatePickerPopupState,
+        id: Id,
+    ) -> bool {
+        popup_state.day = popup_state.day.min(popup_state.last_day_of_month());
+        let saved = self.live_commit && self.commit(popup_state);
+        ui.data_mut(|data| data.insert_persisted(id, popup_state.clone()));
+        saved
+    }
 
-                                                        if day == today {
-                                                            // Encircle today's date
-                                                            let stroke = ui
-                                                                .visuals()
-                                                                .widgets
-                                                                .inactive
-                                                                .fg_stroke;
-                                                            ui.painter().circle_stroke(
-                                                                button_response.rect.center(),
-                                                                8.0,
-                                                                stroke,
-                                                            );
-                                                        }
+    /// Whether the date picked in the popup is valid and enabled, so it may be saved.
+    fn can_save(&self, popup_state: &DatePickerPopupState) -> bool {
+        !popup_state.unpicked && self.precise_date(popup_state).is_some()
+    }
 
-                                                        if button_response.clicked() {
-                                                            popup_state.year = day.year();
-                                                            popup_state.month = day.month();
-                                                            popup_state.day = day.day();
-                                                            ui.data_mut(|data| {
-                                                                data.insert_persisted(
-                                                                    id,
-                                                                    popup_state.clone(),
-                                                                );
-                                                            });
-                                                        }
-                                                    },
-                                                );
+    /// The date to save for the popup state: the picked day if it is enabled, or with
+    /// [`DatePickerButton::precision`] the first enabled day of the picked month or year.
+    fn precise_date(&self, popup_state: &DatePickerPopupState) -> Option<NaiveDate> {
+        self.first_enabled_day(popup_state.date()?, self.precision)
+    }
+
+    /// The first enabled day of the day, month or year of `date`, depending on `unit`.
+    fn first_enabled_day(&self, date: NaiveDate, unit: Precision) -> Option<NaiveDate> {
+        let first = match unit {
+            Precision::Day => return Some(date).filter(|&date| self.is_enabled(date)),
+            Precision::Month => date.with_day(1)?,
+            Precision::Year => date.with_ordinal(1)?,
+        };
+        first
+            .iter_days()
+            .take_while(|day| match unit {
+                Precision::Month => day.month() == first.month() && day.year() == first.year(),
+                _ => day.year() == first.year(),
+            })
+            .find(|&day| self.is_enabled(day))
+    }
+
+    /// The popup state an arrow moves to, or `None` if it would move past the min or max
+    /// date, in which case the arrow is disabled.
+    ///
+    /// A day step must stay within the range. A month or year step only needs an enabled day
+    /// in the month or year it moves to, and then lands on the day clamped into the range.
+    fn arrow_target(
+        &self,
+        popup_state: &DatePickerPopupState,
+        step: NavigationStep,
+    ) -> Option<DatePickerPopupState> {
+        let mut target = popup_state.clone();
+        if !target.navigate(step) {
+            return None;
+        }
+        let date = target.date()?;
+        if self.precision != Precision::Day {
+            return self.precise_date(&target).map(|_| target);
+        }
+        if step.is_day() {
+            let within = if step.is_forward() {
+                self.max_date.map_or(true, |max| date <= max)
+            } else {
+                self.min_date.map_or(true, |min| date >= min)
+            };
+            return within.then_some(target);
+        }
+        self.first_enabled_day(date, step.unit())?;
+        let date = clamp_date(date, self.min_date, self.max_date);
+        target.year = date.year();
+        target.month = date.month();
+        target.day = date.day();
+        Some(target)
+    }
+
+    /// Writes the date (and time) picked in the popup to the selection.
+    ///
+    /// The day is clamped to the month, see [`Self::precise_date`]. Returns `false`, leaving the
+    /// selection as is, if the popup state does not make up a valid date and time or the date
+    /// is disabled.
+    fn commit(&mut self, popup_state: &mut DatePickerPopupState) -> bool {
+        let date = self.precise_date(popup_state);
+        let time =
+            NaiveTime::from_hms_opt(popup_state.hour, popup_state.minute, popup_state.second);
+        let (Some(date), Some(time), false) = (date, time, popup_state.unpicked) else {
+            return false;
+        };
+        popup_state.year = date.year();
+        popup_state.month = date.month();
+        popup_state.day = date.day();
+        *self.selection = date;
+        popup_state.last_seen = Some(date);
+        if let Some(selection_time) = &mut self.time {
+            **selection_time = time;
+            popup_state.last_seen_time = Some(time);
+        }
+        true
+    }
+
+    /// With [`DatePickerButton::highlight_hovered_week`], remembers for the next frame whether
+    /// a cell of `week` is hovered, and paints the background of the cell if the week is.
+    fn hover_week_cell(&self, ui: &Ui, hovered_week_id: Id, week: &Week, week_hovered: bool) {
+        if !self.highlight_hovered_week {
+            return;
+        }
+        if ui.rect_contains_pointer(ui.max_rect()) {
+            ui.data_mut(|data| data.insert_temp(hovered_week_id, week.days[0]));
+        }
+        if week_hovered {
+            ui.painter().rect_filled(
+                ui.max_rect(),
+                0.0,
+                ui.visuals().widgets.inactive.weak_bg_fill,
+            );
+        }
+    }
+
+    /// Draws a single day of the calendar, with `day_ui` if set, and its decoration.
+    fn day_button(
+        &self,
+        ui: &mut Ui,
+        day: NaiveDate,
+        popup_state: &DatePickerPopupState,
+        today: NaiveDate,
+        height: f32,
+        week_hovered: bool,
+    ) -> Response {
+        let state = DayState {
+            selected: !popup_state.unpicked
+                && popup_state.year == day.year()
+                && popup_state.month == day.month()
+                && popup_state.day == day.day(),
+            today: day == today,
+            weekend: day.weekday() == Weekday::Sat || day.weekday() == Weekday::Sun,
+            other_month: day.month() != popup_state.month,
+            enabled: self.is_enabled(day),
+            secondary: self.secondary_date == Some(day),
+        };
+
+        let mut button_response = if let Some(day_ui) = self.day_ui {
+            day_ui(ui, day, state)
+        } else {
+            let highlight = self.highlighted_dates.and_then(|highlight| highlight(day));
+            let fill_color = if state.selected && self.selection_style == SelectionStyle::Fill {
+                ui.visuals().selection.bg_fill
+            } else if let (true, TodayMarker::Fill(color)) = (state.today, self.today_marker) {
+                color
+            } else if let Some(color) = highlight {
+                color
+            } else if state.weekend && self.highlight_weekends {
+                if ui.visuals().dark_mode {
+                    Color32::DARK_RED
+                } else {
+                    Color32::LIGHT_RED
+                }
+            } else if week_hovered {
+                ui.visuals().widgets.inactive.weak_bg_fill
+            } else {
+                ui.visuals().extreme_bg_color
+            };
+
+            let text_color = if state.today && self.today_marker == TodayMarker::Bold {
+                ui.visuals().strong_text_color()
+            } else {
+                ui.visuals().widgets.inactive.text_color()
+            };
+            let mut text_color = readable_text_color(text_color, fill_color);
+
+            if state.other_month {
+                text_color = text_color.linear_multiply(self.adjacent_day_opacity);
+            };
+            if !state.enabled {
+                text_color = text_color.linear_multiply(self.disabled_day_opacity);
+            }
+
+            let text = self
+                .day_number_format
+                .map_or_else(|| day.day().to_string(), |format| format(day.day()));
+            let button_response = ui.add_enabled(
+                state.enabled,
+                Button::new(RichText::new(text).color(text_color)).fill(fill_color),
+            );
+
+            if state.selected && self.selection_style == SelectionStyle::Outline {
+                ui.painter().rect_stroke(
+                    button_response.rect,
+                    ui.visuals().widgets.inactive.corner_radius,
+                    Stroke::new(2.0, ui.visuals().selection.bg_fill),
+                    StrokeKind::Inside,
+                );
+            }
+
+            if state.secondary {
+                // Inside the selection outline, so both can be seen
+                ui.painter().rect_stroke(
+                    button_response.rect.shrink(3.0),
+                    ui.visuals().widgets.inactive.corner_radius,
+                    Stroke::new(1.0, ui.visuals().warn_fg_color),
+                    StrokeKind::Inside,
+                );
+            }
+
+            if state.today {
+                let mut stroke = ui.visuals().widgets.inactive.fg_stroke;
+                match self.today_marker {
+                    TodayMarker::Circle { radius, color } => {
+                        // Encircle today's date
+                        if let Some(color) = color {
+                            stroke.color = color;
+                        }
+                        ui.painter().circle_stroke(
+                            button_response.rect.center(),
+                            radius.unwrap_or(height * 0.4),
+                            stroke,
+                        );
+                    }
+                    TodayMarker::Underline => {
+                        let rect = button_response.rect.shrink2(Vec2::new(height * 0.25, 2.0));
+                        ui.painter().hline(rect.x_range(), rect.bottom(), stroke);
+                    }
+                    TodayMarker::Fill(_) | TodayMarker::Bold | TodayMarker::None => {}
+                }
+            }
+
+            button_response
+        };
+
+        if state.selected && ui.is_enabled() {
+            let focus_day_id = focus_day_id(self.button_id);
+            if ui
+                .data_mut(|data| data.remove_temp::<bool>(focus_day_id))
+                .is_some()
+            {
+                button_response.request_focus();
+            }
+        }
+
+        // Screen readers get the full date instead of just the day number
+        button_response.widget_info(|| {
+            let mut label = day.format("%A, %B %-d, %Y").to_string();
+            if state.today {
+                label.push_str(", today");
+            }
+            WidgetInfo::selected(WidgetType::Button, state.enabled, state.selected, label)
+        });
+
+        let mut hover_text = String::new();
+        if let Some(format) = self.hover_format {
+            // Skip formats chrono cannot show rather than panicking
+            if write!(hover_text, "{}", day.format(format)).is_err() {
+                hover_text.clear();
+            }
+        }
+        if self.week_number_on_hover {
+            if !hover_text.is_empty() {
+                hover_text.push('\n');
+            }
+            hover_text.push_str(&format!("Week {}", self.week_numbering.week_number(day)));
+        }
+        if !hover_text.is_empty() {
+            button_response = button_response.on_hover_text(hover_text);
+        }
+
+        if let Some(decorator) = self.day_decorator {
+            decorator(day, ui, button_response.rect);
+        }
+
+        button_response
+    }
+
+    /// Draws the year or month grid shown in place of the calendar in quick navigation mode.
+    /// Returns whether the date was saved, see [`DatePickerButton::live_commit`].
+    fn quick_nav_grid(
+        &mut self,
+        ui: &mut Ui,
+        popup_state: &mut DatePickerPopupState,
+        id: Id,
+    ) -> bool {
+        let mut saved = false;
+        let view = popup_state.view;
+        let (rows, columns) = match view {
+            CalendarView::Days => return false,
+            CalendarView::Months => (4, 3),
+            CalendarView::Years => (4, 4),
+        };
+        // Show the decade of the current year together with a few years around it, or the
+        // years before or after that page by page
+        let years = (rows * columns) as i32;
+        let first_year =
+            popup_state.year - popup_state.year.rem_euclid(10) - 3 + years * popup_state.years_page;
+        let last_year = first_year + years - 1;
+        let paging = view == CalendarView::Years;
+
+        StripBuilder::new(ui)
+            .sizes(Size::remainder(), rows + paging as usize)
+            .vertical(|mut strip| {
+                if paging {
+                    strip.strip(|builder| {
+                        builder.sizes(Size::remainder(), 3).horizontal(|mut strip| {
+                            for (page, glyph, label) in
+                                [(-1, "<", "Previous years"), (1, ">", "Next years")]
+                            {
+                                // Page as long as the grid keeps some year that can be picked
+                                let enabled = (first_year + years * page
+                                    ..=last_year + years * page)
+                                    .any(|year| self.is_year_in_range(year));
+                                strip.cell(|ui| {
+                                    ui.with_layout(
+                                        Layout::top_down_justified(Align::Center),
+                                        |ui| {
+                                            let response = ui
+                                                .add_enabled(enabled, Button::new(glyph))
+                                                .on_hover_text(label);
+                                            response.widget_info(|| {
+                                                WidgetInfo::labeled(
+                                                    WidgetType::Button,
+                                                    enabled,
+                                                    label,
+                                                )
                                             });
-                                        }
+                                            if response.clicked() {
+                                                popup_state.years_page += page;
+                                                ui.data_mut(|data| {
+                                                    data.insert_persisted(id, popup_state.clone());
+                                                });
+                                            }
+                                        },
+                                    );
+                                });
+                                if page < 0 {
+                                    strip.cell(|ui| {
+                                        ui.centered_and_justified(|ui| {
+                                            ui.label(format!("{first_year}–{last_year}"));
+                                        });
                                     });
                                 }
-                            });
+                            }
+                        });
                     });
                 }
+                for row in 0..rows {
+                    strip.strip(|builder| {
+                        builder
+                            .sizes(Size::remainder(), columns)
+                            .horizontal(|mut strip| {
+                                for column in 0..columns {
+                                    let index = row * columns + column;
+                                    strip.cell(|ui| {
+                                        ui.with_layout(
+                                            Layout::top_down_justified(Align::Center),
+                                            |ui| {
+                                                let (text, selected, enabled) = match view {
+                                                    CalendarView::Years => {
+                                                        let year = first_year + index as i32;
+                                                        (
+                                                            year.to_string(),
+                                                            year == popup_state.year,
+                                                            self.is_year_in_range(year),
+                                                        )
+                                                    }
+                                                    _ => {
+                                                        let month = index as u32 + 1;
+                                                        (
+                                                            month_name(
+                                                                month as usize,
+                                                                self.month_names,
+                                                            )
+                                                            .to_owned(),
+                                                            month == popup_state.month,
+                                                            NaiveDate::from_ymd_opt(
+                                                                popup_state.year,
+                                                                month,
+                                                                1,
+                                                            )
+                                                            .and_then(|first| {
+                                                                self.first_enabled_day(
+                                                                    first,
+                                                                    Precision::Month,
+                                                                )
+                                                            })
+                                                            .is_some(),
+                                                        )
+                                                    }
+                                                };
 
-                strip.strip(|builder| {
-                    builder.sizes(Size::remainder(), 3).horizontal(|mut strip| {
-                        strip.empty();
-                        strip.cell(|ui| {
-                            ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
-                                if ui.button(self.cancel_button_text).clicked() {
-                                    close = true;
-                                }
-                            });
-                        });
-                        strip.cell(|ui| {
-                            ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
-                                if ui.button(self.save_button_text).clicked() {
-                                    *self.selection = NaiveDate::from_ymd_opt(
-                                        popup_state.year,
-                                        popup_state.month,
-                                        popup_state.day,
-                                    )
-                                    .expect("Could not create NaiveDate");
-                                    saved = true;
-                                    close = true;
+                                                let mut button = Button::new(text);
+                                                if selected {
+                                                    button =
+                                                        button.fill(ui.visuals().selection.bg_fill);
+                                                }
+
+                                                if ui.add_enabled(enabled, button).clicked() {
+                                                    match view {
+                                                        CalendarView::Years => {
+                                                            popup_state.year =
+                                                                first_year + index as i32;
+                                                        }
+                                                        _ => popup_state.month = index as u32 + 1,
+                                                    }
+                                                    popup_state.day = popup_state
+                                                        .day
+                                                        .min(popup_state.last_day_of_month());
+                                                    popup_state.view = CalendarView::Days;
+                                                    if self.live_commit {
+                                                        saved = self.commit(popup_state);
+                                                    }
+                                                    ui.data_mut(|data| {
+                                                        data.insert_persisted(
+                                                            id,
+                                                            popup_state.clone(),
+                                                        );
+                                                    });
+                                                }
+                                            },
+                                        );
+                                    });
                                 }
                             });
-                        });
                     });
-                });
+                }
             });
+        saved
+    }
+}
 
-        if close {
-            popup_state.setup = false;
-            ui.data_mut(|data| {
-                data.insert_persisted(id, popup_state);
-                data.get_persisted_mut_or_default::<DatePickerButtonState>(self.button_id)
-                    .picker_visible = false;
-            });
-        }
+/// Combo box for one component of the time of day. Returns `true` if it was changed.
+fn time_combo_box(ui: &mut Ui, id_salt: Id, value: &mut u32, count: u32) -> bool {
+    let mut changed = false;
+    ComboBox::from_id_salt(id_salt)
+        .width(ui.available_width())
+        .selected_text(format!("{value:02}"))
+        .show_ui(ui, |ui| {
+            let opened = combo_list_opened(ui);
+            for v in 0..count {
+                let selected = *value == v;
+                let response = ui.selectable_value(value, v, format!("{v:02}"));
+                if opened && selected {
+                    response.scroll_to_me_animation(Some(Align::Center), ScrollAnimation::none());
+                }
+                changed |= response.changed();
+            }
+        });
+    changed
+}
+
+/// Drag value for one component of the time of day. Returns `true` if it was changed.
+fn time_drag_value(ui: &mut Ui, value: &mut u32, count: u32) -> bool {
+    ui.centered_and_justified(|ui| {
+        ui.add(
+            DragValue::new(value)
+                .range(0..=count - 1)
+                .custom_formatter(|v, _| format!("{v:02}")),
+        )
+        .changed()
+    })
+    .inner
+}
+
+/// How far to step the value of a closed combo box: by one for each notch of a mouse wheel
+/// scrolled over it, or for the up and down arrows while it has focus. Up steps to the next
+/// value.
+fn combo_box_step(ui: &Ui, response: &Response) -> i32 {
+    if ui.memory(|mem| mem.any_popup_open()) {
+        return 0;
+    }
+    let mut step = 0;
+    let scrolled_id = response.id.with("scrolled");
+    if response.hovered() {
+        // The combo box takes the scrolling, so a scrolling popup does not move along
+        let scroll = ui.input_mut(|i| {
+            let scroll = i.raw_scroll_delta.y;
+            i.raw_scroll_delta.y = 0.0;
+            i.smooth_scroll_delta.y = 0.0;
+            scroll
+        });
+        // Trackpads scroll a little every frame, so sum that up to whole notches
+        let notch = ui.ctx().options(|o| o.line_scroll_speed).max(1.0);
+        let scrolled = ui
+            .data(|data| data.get_temp::<f32>(scrolled_id))
+            .unwrap_or_default()
+            + scroll;
+        let notches = (scrolled / notch).trunc();
+        step += notches as i32;
+        ui.data_mut(|data| data.insert_temp(scrolled_id, scrolled - notches * notch));
+    } else {
+        ui.data_mut(|data| data.remove_temp::<f32>(scrolled_id));
+    }
+    if response.has_focus() {
+        ui.input_mut(|i| {
+            if i.consume_key(Modifiers::NONE, Key::ArrowUp) {
+                step += 1;
+            }
+            if i.consume_key(Modifiers::NONE, Key::ArrowDown) {
+                step -= 1;
+            }
+        });
+    }
+    step
+}
 
-        saved && close
+/// Returns `true` in the first pass a combo box list is shown, to scroll its selected item
+/// into view. Call it inside `show_ui` once per pass.
+fn combo_list_opened(ui: &Ui) -> bool {
+    // The list is measured in a sizing pass first, which ignores scrolling
+    if ui.is_sizing_pass() {
+        return true;
     }
+    let id = ui.id().with("date_picker_combo_opened");
+    let pass = ui.ctx().cumulative_pass_nr();
+    let last_pass = ui.data_mut(|data| {
+        let last_pass = data.get_temp::<u64>(id);
+        data.insert_temp(id, pass);
+        last_pass
+    });
+    last_pass.map_or(true, |last_pass| last_pass + 1 < pass)
+}
+
+/// Relative luminance of a color, as defined by WCAG.
+fn luminance(color: Color32) -> f32 {
+    let rgba = Rgba::from(color);
+    0.2126 * rgba.r() + 0.7152 * rgba.g() + 0.0722 * rgba.b()
+}
+
+/// Returns `text` if it is readable on `fill`, otherwise black or white, whichever contrasts
+/// more. Custom fills, e.g. from [`DatePickerButton::highlighted_dates`], can be too close to
+/// the text color of the theme.
+fn readable_text_color(text: Color32, fill: Color32) -> Color32 {
+    let contrast = |a: Color32, b: Color32| {
+        let (a, b) = (luminance(a), luminance(b));
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    };
+    if contrast(text, fill) >= 3.0 {
+        text
+    } else if contrast(Color32::BLACK, fill) > contrast(Color32::WHITE, fill) {
+        Color32::BLACK
+    } else {
+        Color32::WHITE
+    }
+}
+
+/// The years the popup can show. The first and last year of the range of [`NaiveDate`] are
+/// left out, as the calendar shows full weeks, which reach beyond the range there.
+fn supported_years() -> RangeInclusive<i32> {
+    NaiveDate::MIN.year() + 1..=NaiveDate::MAX.year() - 1
 }
 
 fn month_name(i: usize, months: &[&'static str]) -> &'static str {
     months[i - 1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// Checks that the calendar of the month is made of the full weeks starting on `mondays`.
+    fn assert_weeks(year: i32, month: u32, mondays: &[NaiveDate]) {
+        let weeks = calendar_weeks(year, month, WeekNumbering::Iso).unwrap();
+        let days: Vec<Vec<NaiveDate>> = weeks.into_iter().map(|week| week.days).collect();
+        let expected: Vec<Vec<NaiveDate>> = mondays
+            .iter()
+            .map(|monday| monday.iter_days().take(7).collect())
+            .collect();
+        assert_eq!(days, expected);
+    }
+
+    #[test]
+    fn calendar_weeks_month_starting_on_sunday() {
+        // The first of September 2024 is the last day of its week
+        assert_weeks(
+            2024,
+            9,
+            &[
+                date(2024, 8, 26),
+                date(2024, 9, 2),
+                date(2024, 9, 9),
+                date(2024, 9, 16),
+                date(2024, 9, 23),
+                date(2024, 9, 30),
+            ],
+        );
+    }
+
+    #[test]
+    fn calendar_weeks_month_ending_on_monday() {
+        // March 31, 2025 is the only day of March in the last week
+        assert_weeks(
+            2025,
+            3,
+            &[
+                date(2025, 2, 24),
+                date(2025, 3, 3),
+                date(2025, 3, 10),
+                date(2025, 3, 17),
+                date(2025, 3, 24),
+                date(2025, 3, 31),
+            ],
+        );
+    }
+
+    #[test]
+    fn calendar_weeks_leap_year_february() {
+        // February 29 is on a Thursday, so the last week ends on March 3
+        assert_weeks(
+            2024,
+            2,
+            &[
+                date(2024, 1, 29),
+                date(2024, 2, 5),
+                date(2024, 2, 12),
+                date(2024, 2, 19),
+                date(2024, 2, 26),
+            ],
+        );
+    }
+
+    #[test]
+    fn calendar_weeks_february_starting_on_monday() {
+        // A February of 28 days starting on Monday is the only month without adjacent days
+        assert_weeks(
+            2021,
+            2,
+            &[
+                date(2021, 2, 1),
+                date(2021, 2, 8),
+                date(2021, 2, 15),
+                date(2021, 2, 22),
+            ],
+        );
+    }
+
+    #[test]
+    fn calendar_weeks_31_day_month_ending_on_sunday() {
+        // No extra week after October 31, 2021
+        assert_weeks(
+            2021,
+            10,
+            &[
+                date(2021, 9, 27),
+                date(2021, 10, 4),
+                date(2021, 10, 11),
+                date(2021, 10, 18),
+                date(2021, 10, 25),
+            ],
+        );
+    }
+
+    #[test]
+    fn calendar_weeks_cover_the_month() {
+        // The Gregorian calendar repeats every 400 years
+        for year in 2000..2400 {
+            for month in 1..=12 {
+                let weeks = calendar_weeks(year, month, WeekNumbering::Iso).unwrap();
+                assert!((4..=6).contains(&weeks.len()), "{year}-{month}");
+                let first = date(year, month, 1);
+                let last = first + Months::new(1) - Duration::days(1);
+                assert!(weeks[0].days.contains(&first), "{year}-{month}");
+                assert!(
+                    weeks[weeks.len() - 1].days.contains(&last),
+                    "{year}-{month}"
+                );
+            }
+        }
+    }
+
+    /// The number and year of each week of the calendar of the month.
+    fn week_numbers(year: i32, month: u32, week_numbering: WeekNumbering) -> Vec<(u8, i32)> {
+        calendar_weeks(year, month, week_numbering)
+            .unwrap()
+            .into_iter()
+            .map(|week| (week.number, week.year))
+            .collect()
+    }
+
+    #[test]
+    fn iso_week_numbers_in_early_january() {
+        // January 1, 2021 is a Friday, so the first row is the last week of 2020
+        assert_eq!(
+            week_numbers(2021, 1, WeekNumbering::Iso),
+            [(53, 2020), (1, 2021), (2, 2021), (3, 2021), (4, 2021)]
+        );
+        // January 1, 2025 is a Wednesday, so the first row is week 1 already
+        assert_eq!(
+            week_numbers(2025, 1, WeekNumbering::Iso),
+            [(1, 2025), (2, 2025), (3, 2025), (4, 2025), (5, 2025)]
+        );
+        // The same week ends December 2024
+        assert_eq!(week_numbers(2024, 12, WeekNumbering::Iso)[5], (1, 2025));
+    }
+
+    #[test]
+    fn us_week_numbers_in_early_january() {
+        // Week 1 is the week with January 1, even if most of it is in December
+        assert_eq!(
+            week_numbers(2021, 1, WeekNumbering::UsCommon),
+            [(1, 2021), (2, 2021), (3, 2021), (4, 2021), (5, 2021)]
+        );
+        assert_eq!(
+            week_numbers(2025, 1, WeekNumbering::UsCommon),
+            [(1, 2025), (2, 2025), (3, 2025), (4, 2025), (5, 2025)]
+        );
+        assert_eq!(
+            week_numbers(2020, 12, WeekNumbering::UsCommon)[4],
+            (1, 2021)
+        );
+        assert_eq!(
+            week_numbers(2024, 12, WeekNumbering::UsCommon)[5],
+            (1, 2025)
+        );
+    }
+
+    #[test]
+    fn month_data_cache_is_hit_while_the_month_stays() {
+        let mut cache = MonthDataCache::default();
+        let march = (2024, 3, WeekNumbering::Iso);
+        let weeks = cache.get(march);
+        // The next frames showing the same month get the same weeks, without computing them
+        for _ in 0..3 {
+            cache.evict_cache();
+            assert!(Arc::ptr_eq(&weeks, &cache.get(march)));
+        }
+        // Another month or numbering is a new key
+        assert!(!Arc::ptr_eq(
+            &weeks,
+            &cache.get((2024, 4, WeekNumbering::Iso))
+        ));
+        assert!(!Arc::ptr_eq(
+            &weeks,
+            &cache.get((2024, 3, WeekNumbering::UsCommon))
+        ));
+    }
+
+    #[test]
+    fn readable_text_color_keeps_the_theme_on_default_fills() {
+        for (visuals, weekend) in [
+            (egui::Visuals::dark(), Color32::DARK_RED),
+            (egui::Visuals::light(), Color32::LIGHT_RED),
+        ] {
+            let fills = [
+                visuals.extreme_bg_color,
+                visuals.selection.bg_fill,
+                visuals.widgets.inactive.weak_bg_fill,
+                weekend,
+            ];
+            for text in [
+                visuals.widgets.inactive.text_color(),
+                visuals.strong_text_color(),
+            ] {
+                for fill in fills {
+                    assert_eq!(
+                        readable_text_color(text, fill),
+                        text,
+                        "{text:?} on {fill:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn readable_text_color_picks_black_or_white_on_custom_fills() {
+        let dark_text = egui::Visuals::light().widgets.inactive.text_color();
+        let light_text = egui::Visuals::dark().widgets.inactive.text_color();
+        // Light text on a light fill, and dark text on a dark fill
+        assert_eq!(
+            readable_text_color(light_text, Color32::YELLOW),
+            Color32::BLACK
+        );
+        assert_eq!(
+            readable_text_color(light_text, Color32::from_rgb(255, 220, 240)),
+            Color32::BLACK
+        );
+        assert_eq!(
+            readable_text_color(dark_text, Color32::DARK_BLUE),
+            Color32::WHITE
+        );
+        assert_eq!(
+            readable_text_color(dark_text, Color32::from_rgb(40, 0, 60)),
+            Color32::WHITE
+        );
+    }
+
+    #[test]
+    fn clamp_date_moves_the_date_into_the_range() {
+        let (min, max) = (Some(date(2024, 3, 1)), Some(date(2024, 3, 31)));
+        assert_eq!(clamp_date(date(2024, 3, 15), min, max), date(2024, 3, 15));
+        assert_eq!(clamp_date(date(2024, 2, 10), min, max), date(2024, 3, 1));
+        assert_eq!(clamp_date(date(2024, 4, 10), min, max), date(2024, 3, 31));
+    }
+
+    #[test]
+    fn clamp_date_with_missing_bounds() {
+        let day = date(2024, 3, 15);
+        assert_eq!(clamp_date(day, None, None), day);
+        assert_eq!(
+            clamp_date(day, Some(date(2024, 4, 1)), None),
+            date(2024, 4, 1)
+        );
+        assert_eq!(clamp_date(day, Some(date(2024, 3, 1)), None), day);
+        assert_eq!(
+            clamp_date(day, None, Some(date(2024, 3, 1))),
+            date(2024, 3, 1)
+        );
+        assert_eq!(clamp_date(day, None, Some(date(2024, 4, 1))), day);
+    }
+
+    #[test]
+    fn clamp_date_min_wins_over_max() {
+        let (min, max) = (Some(date(2024, 3, 20)), Some(date(2024, 3, 10)));
+        for day in [date(2024, 3, 1), date(2024, 3, 15), date(2024, 3, 31)] {
+            assert_eq!(clamp_date(day, min, max), date(2024, 3, 20));
+        }
+    }
+
+    #[test]
+    fn formatted_date_parses_back() {
+        let mut day = date(2024, 2, 29);
+        let button = DatePickerButton::new(&mut day);
+        assert_eq!(button.formatted().as_deref(), Some("2024-02-29"));
+        assert_eq!(button.parse("2024-02-29"), Some(date(2024, 2, 29)));
+        assert_eq!(button.parse("2024-02-30"), None);
+
+        let button = button.format("%d.%m.%Y");
+        let text = button.formatted().unwrap();
+        assert_eq!(text, "29.02.2024");
+        assert_eq!(button.parse(&text), Some(date(2024, 2, 29)));
+    }
+
+    #[test]
+    fn formatted_date_time_parses_back() {
+        let mut date_time = date(2024, 2, 29).and_hms_opt(13, 45, 0).unwrap();
+        let button = DatePickerButton::new_with_time(&mut date_time);
+        let text = button.formatted().unwrap();
+        assert_eq!(text, "2024-02-29 13:45");
+        assert_eq!(button.parse(&text), Some(date(2024, 2, 29)));
+        assert_eq!(button.parse("2024-02-29 25:00"), None);
+    }
+
+    #[test]
+    fn formatted_month_and_year_parse_back() {
+        let mut day = date(2024, 2, 1);
+        let button = DatePickerButton::new(&mut day).precision(Precision::Month);
+        let text = button.formatted().unwrap();
+        assert_eq!(text, "2024-02");
+        assert_eq!(button.parse(&text), Some(date(2024, 2, 1)));
+
+        let button = button.precision(Precision::Year);
+        let text = button.formatted().unwrap();
+        assert_eq!(text, "2024");
+        assert_eq!(button.parse(&text), Some(date(2024, 1, 1)));
+
+        let button = button.precision(Precision::Day);
+        assert_eq!(button.formatted().unwrap(), "2024-02-01");
+        let button = button.format("%Y-%m-%d").precision(Precision::Month);
+        assert_eq!(button.formatted().unwrap(), "2024-02-01");
+    }
+
+    #[test]
+    fn navigating_stops_at_the_supported_years() {
+        let (floor, ceiling) = supported_years().into_inner();
+        let mut state = DatePickerPopupState {
+            year: floor + 1,
+            month: 1,
+            day: 1,
+            ..Default::default()
+        };
+        assert!(state.navigate(NavigationStep::PreviousYear));
+        assert_eq!((state.year, state.month, state.day), (floor, 1, 1));
+        for step in [
+            NavigationStep::PreviousYear,
+            NavigationStep::PreviousMonth,
+            NavigationStep::PreviousDay,
+        ] {
+            assert!(!state.navigate(step));
+            assert_eq!((state.year, state.month, state.day), (floor, 1, 1));
+        }
+        assert!(calendar_weeks(floor, 1, WeekNumbering::Iso).is_some());
+
+        let mut state = DatePickerPopupState {
+            year: ceiling,
+            month: 12,
+            day: 31,
+            ..Default::default()
+        };
+        for step in [
+            NavigationStep::NextDay,
+            NavigationStep::NextMonth,
+            NavigationStep::NextYear,
+        ] {
+            assert!(!state.navigate(step));
+            assert_eq!((state.year, state.month, state.day), (ceiling, 12, 31));
+        }
+        assert!(calendar_weeks(ceiling, 12, WeekNumbering::Iso).is_some());
+    }
+
+    #[test]
+    fn saving_day_31_in_february_clamps_the_day() {
+        let config = DatePickerButton::unbound().config;
+        for (year, last_day) in [(2023, 28), (2024, 29)] {
+            let mut selection = date(2024, 1, 1);
+            let mut popup_state = DatePickerPopupState {
+                year,
+                month: 2,
+                day: 31,
+                ..Default::default()
+            };
+            let saved = config
+                .popup(&mut selection, None, Id::NULL, true, (None, None))
+                .commit(&mut popup_state);
+            assert!(saved);
+            assert_eq!(selection, date(year, 2, last_day));
+            assert_eq!(popup_state.day, last_day);
+        }
+    }
+
+    /// The header row of the calendar of `config`.
+    fn header(config: &ButtonConfig<'_>) -> Vec<String> {
+        let mut selection = date(2024, 1, 1);
+        let popup = config.popup(&mut selection, None, Id::NULL, true, (None, None));
+        popup
+            .columns()
+            .into_iter()
+            .map(|column| popup.header_label(column))
+            .collect()
+    }
+
+    #[test]
+    fn right_to_left_reverses_the_header() {
+        let config = DatePickerButton::unbound();
+        assert_eq!(
+            header(&config.config),
+            ["Week", "Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"]
+        );
+        let config = config.right_to_left(true);
+        assert_eq!(
+            header(&config.config),
+            ["Su", "Sa", "Fr", "Th", "We", "Tu", "Mo", "Week"]
+        );
+        let config = config.calendar_week(false);
+        assert_eq!(
+            header(&config.config),
+            ["Su", "Sa", "Fr", "Th", "We", "Tu", "Mo"]
+        );
+    }
+
+    #[test]
+    fn parse_fills_in_a_missing_day_and_month() {
+        assert_eq!(parse("%d.%m.%Y", "29.02.2024"), Some(date(2024, 2, 29)));
+        assert_eq!(parse("%Y-%m", "2024-02"), Some(date(2024, 2, 1)));
+        assert_eq!(parse("%Y", "2024"), Some(date(2024, 1, 1)));
+        assert_eq!(parse("%G-W%V-%u", "2025-W01-1"), Some(date(2024, 12, 30)));
+        assert_eq!(parse("%Y-%m-%d", "2024-02-30"), None);
+        assert_eq!(parse("%Y-%m-%d", "2024-02"), None);
+    }
+}
@@ -0,0 +1,230 @@
+use chrono::{NaiveDate, Weekday};
+use egui::{accesskit::Role, Key};
+use egui_extras_datepicker_fork::{DatePickerButton, DEFAULT_PRESETS};
+use egui_kittest::{kittest::Queryable, Harness};
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+/// A harness showing a date picker button for the date in its state.
+fn harness(selection: NaiveDate) -> Harness<'static, NaiveDate> {
+    harness_with(selection, |button| button)
+}
+
+/// A harness showing a date picker button for the date in its state, configured by `config`.
+fn harness_with(
+    selection: NaiveDate,
+    config: for<'a> fn(DatePickerButton<'a>) -> DatePickerButton<'a>,
+) -> Harness<'static, NaiveDate> {
+    Harness::new_ui_state(
+        move |ui, selection| {
+            ui.add(config(DatePickerButton::new(selection)));
+        },
+        selection,
+    )
+}
+
+/// Opens the popup by clicking the button, the only widget before it opens.
+fn open(harness: &mut Harness<'_, NaiveDate>) {
+    harness.get_by_role(Role::Button).click();
+    harness.run();
+}
+
+#[test]
+fn saving_a_picked_day_sets_the_date() {
+    let mut harness = harness(date(2024, 3, 10));
+    open(&mut harness);
+    harness.get_by_label("Wednesday, March 20, 2024").click();
+    harness.run();
+    harness.get_by_label("Save").click();
+    harness.run();
+    assert_eq!(*harness.state(), date(2024, 3, 20));
+}
+
+#[test]
+fn escape_leaves_the_date_unchanged() {
+    let mut harness = harness(date(2024, 3, 10));
+    open(&mut harness);
+    harness.get_by_label("Wednesday, March 20, 2024").click();
+    harness.run();
+    harness.press_key(Key::Escape);
+    harness.run();
+    assert_eq!(*harness.state(), date(2024, 3, 10));
+    assert!(harness.query_by_label("Save").is_none());
+}
+
+#[test]
+fn changing_the_date_while_open_shows_the_new_date() {
+    let mut harness = harness(date(2024, 3, 10));
+    open(&mut harness);
+    *harness.state_mut() = date(2025, 7, 1);
+    harness.run();
+    assert!(harness.query_by_label("Tuesday, July 15, 2025").is_some());
+    assert!(harness
+        .query_by_label("Wednesday, March 20, 2024")
+        .is_none());
+    harness.get_by_label("Save").click();
+    harness.run();
+    assert_eq!(*harness.state(), date(2025, 7, 1));
+}
+
+#[test]
+fn changing_the_date_while_closed_shows_it_on_reopening() {
+    let mut harness = harness(date(2024, 3, 10));
+    open(&mut harness);
+    harness.get_by_label("Next month").click();
+    harness.run();
+    harness.press_key(Key::Escape);
+    harness.run();
+    *harness.state_mut() = date(2025, 7, 1);
+    harness.run();
+    open(&mut harness);
+    assert!(harness.query_by_label("Tuesday, July 15, 2025").is_some());
+    assert!(harness.query_by_label("Saturday, April 20, 2024").is_none());
+}
+
+#[test]
+fn picking_a_day_of_an_unselectable_weekday_does_nothing() {
+    let mut harness = harness_with(date(2024, 3, 11), |button| {
+        button.selectable_weekdays(&[
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ])
+    });
+    open(&mut harness);
+    let saturday = harness.get_by_label("Saturday, March 16, 2024");
+    assert!(saturday.is_disabled());
+    saturday.click();
+    harness.run();
+    harness.get_by_label("Save").click();
+    harness.run();
+    assert_eq!(*harness.state(), date(2024, 3, 11));
+}
+
+#[test]
+fn two_pickers_navigate_independently() {
+    let mut harness = Harness::new_ui_state(
+        |ui, (start, end): &mut (NaiveDate, NaiveDate)| {
+            ui.add(DatePickerButton::new(start).id_salt("start"));
+            ui.add(DatePickerButton::new(end).id_salt("end"));
+        },
+        (date(2024, 3, 10), date(2024, 3, 10)),
+    );
+    harness
+        .get_all_by_role(Role::Button)
+        .next()
+        .unwrap()
+        .click();
+    harness.run();
+    harness.get_by_label("Next month").click();
+    harness.run();
+    assert!(harness.query_by_label("Saturday, April 20, 2024").is_some());
+
+    harness.press_key(Key::Escape);
+    harness.run();
+    harness
+        .get_all_by_role(Role::Button)
+        .nth(1)
+        .unwrap()
+        .click();
+    harness.run();
+    assert!(harness
+        .query_by_label("Wednesday, March 20, 2024")
+        .is_some());
+    assert!(harness.query_by_label("Saturday, April 20, 2024").is_none());
+}
+
+#[test]
+fn saving_the_same_date_does_not_report_a_change() {
+    let mut harness = Harness::new_ui_state(
+        |ui, (selection, changed): &mut (NaiveDate, bool)| {
+            *changed |= ui.add(DatePickerButton::new(selection)).changed();
+        },
+        (date(2024, 3, 10), false),
+    );
+    harness.get_by_role(Role::Button).click();
+    harness.run();
+    harness.get_by_label("Save").click();
+    harness.run();
+    assert!(harness.query_by_label("Save").is_none());
+    assert_eq!(*harness.state(), (date(2024, 3, 10), false));
+
+    harness.get_by_role(Role::Button).click();
+    harness.run();
+    harness.get_by_label("Wednesday, March 20, 2024").click();
+    harness.run();
+    harness.get_by_label("Save").click();
+    harness.run();
+    assert_eq!(*harness.state(), (date(2024, 3, 20), true));
+}
+
+#[test]
+fn arrows_past_the_min_date_are_disabled() {
+    let mut harness = harness_with(date(2024, 3, 10), |button| {
+        button.min_date(date(2024, 3, 10))
+    });
+    open(&mut harness);
+    for label in ["Previous year", "Previous month", "Previous day"] {
+        assert!(harness.get_by_label(label).is_disabled(), "{label}");
+    }
+    for label in ["Next day", "Next month", "Next year"] {
+        assert!(!harness.get_by_label(label).is_disabled(), "{label}");
+    }
+}
+
+#[test]
+fn an_invalid_format_is_shown_instead_of_the_date() {
+    let harness = harness_with(date(2024, 3, 10), |button| {
+        button.show_icon(false).format("%Y %Q")
+    });
+    harness.get_by_label("%Y %Q");
+}
+
+#[test]
+fn a_month_arrow_lands_on_the_min_date() {
+    let mut harness = harness_with(date(2024, 4, 5), |button| {
+        button.min_date(date(2024, 3, 10))
+    });
+    open(&mut harness);
+    assert!(harness.get_by_label("Previous year").is_disabled());
+    harness.get_by_label("Previous month").click();
+    harness.run();
+    assert!(harness.get_by_label("Previous month").is_disabled());
+    harness.get_by_label("Save").click();
+    harness.run();
+    assert_eq!(*harness.state(), date(2024, 3, 10));
+}
+
+#[test]
+fn presets_in_the_past_are_disabled_with_disable_past() {
+    let mut harness = harness_with(date(2024, 3, 10), |button| {
+        button.presets(DEFAULT_PRESETS).disable_past(true)
+    });
+    open(&mut harness);
+    assert!(harness.get_by_label("Yesterday").is_disabled());
+    assert!(!harness.get_by_label("Today").is_disabled());
+}
+
+#[test]
+fn quick_nav_grids_page_and_skip_months_before_the_min_date() {
+    let mut harness = harness_with(date(2024, 3, 10), |button| {
+        button.quick_nav(true).min_date(date(2024, 3, 10))
+    });
+    open(&mut harness);
+    harness.get_by_label("March").click();
+    harness.run();
+    assert!(harness.get_by_label("February").is_disabled());
+    assert!(!harness.get_by_label("April").is_disabled());
+
+    harness.get_by_label("2024").click();
+    harness.run();
+    assert!(harness.query_by_label("2017–2032").is_some());
+    assert!(harness.get_by_label("Previous years").is_disabled());
+    harness.get_by_label("Next years").click();
+    harness.run();
+    assert!(harness.query_by_label("2033–2048").is_some());
+}